@@ -0,0 +1,383 @@
+//! Static analysis of parsed JSONPath queries
+use std::collections::BTreeSet;
+
+use serde_json_path_core::spec::{
+    functions::{FunctionExpr, FunctionExprArg, Validated},
+    query::{Query, QueryKind},
+    segment::Segment,
+    selector::{
+        filter::{
+            ArrayOperand, BasicExpr, Comparable, LogicalOrExpr, SingularQuery, SingularQueryKind,
+        },
+        Selector,
+    },
+};
+
+/// The result of statically analyzing a [`JsonPath`][crate::JsonPath]
+///
+/// This is produced by the [`JsonPath::analyze`][crate::JsonPath::analyze] method, and can be used
+/// to inspect a query before it is ever evaluated, e.g., to reject queries that are obviously too
+/// expensive to run against large documents.
+///
+/// # Example
+/// ```rust
+/// # use serde_json_path::JsonPath;
+/// # fn main() -> Result<(), serde_json_path::ParseError> {
+/// let path = JsonPath::parse("$..book[?@.price < $.limit && length(@.title) > 10]")?;
+/// let analysis = path.analyze();
+/// assert!(!analysis.is_singular());
+/// assert_eq!(analysis.descendant_segments(), 1);
+/// assert_eq!(analysis.filter_depth(), 1);
+/// assert_eq!(analysis.absolute_subqueries(), ["$['limit']"]);
+/// assert_eq!(analysis.functions(), ["length"]);
+/// assert_eq!(analysis.cost().to_string(), "O(n)");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryAnalysis {
+    singular: bool,
+    descendant_segments: usize,
+    filter_depth: usize,
+    absolute_subqueries: Vec<String>,
+    functions: Vec<String>,
+    cost: CostBound,
+}
+
+impl QueryAnalysis {
+    /// Will the query produce at most one node
+    ///
+    /// A query is singular if it contains no descendant segments, and each of its segments
+    /// contains a single name or index selector.
+    pub fn is_singular(&self) -> bool {
+        self.singular
+    }
+
+    /// The number of descendant (`..`) segments in the query
+    ///
+    /// This includes descendant segments used in queries within filter expressions.
+    pub fn descendant_segments(&self) -> usize {
+        self.descendant_segments
+    }
+
+    /// The maximum nesting depth of filter selectors in the query
+    ///
+    /// A query without any filters has a depth of `0`, while a query whose filters contain
+    /// queries that themselves contain filters, e.g., `$[?@[?@.a]]`, has a depth of `2`.
+    pub fn filter_depth(&self) -> usize {
+        self.filter_depth
+    }
+
+    /// The absolute (`$`) queries used within filter expressions, in the order they appear
+    ///
+    /// Each entry is formatted using the canonical form of the query. Absolute queries within
+    /// filters are evaluated against the root of the document for every node being filtered, and
+    /// therefore can have a large impact on the cost of a query.
+    pub fn absolute_subqueries(&self) -> &[String] {
+        &self.absolute_subqueries
+    }
+
+    /// The names of the functions used in the query, sorted and without duplicates
+    pub fn functions(&self) -> &[String] {
+        &self.functions
+    }
+
+    /// An upper bound on the cost of evaluating the query
+    ///
+    /// See [`CostBound`] for details on how the cost is modeled.
+    pub fn cost(&self) -> CostBound {
+        self.cost
+    }
+}
+
+/// An upper bound on the cost of evaluating a query, relative to the size of the queried document
+///
+/// The cost is modeled as the number of nodes visited during evaluation, expressed as a power of
+/// `n`, the total number of nodes in the queried document. For example:
+///
+/// * `$.foo.bar` visits a fixed number of nodes, regardless of the document size: `O(1)`
+/// * `$.foo[*]` and `$..bar` may visit every node in the document: `O(n)`
+/// * `$..foo..bar` may visit every node once for each of its ancestors: `O(n^2)`
+/// * `$[?count($..b) == 1]` evaluates an `O(n)` query for each of up to `n` nodes: `O(n^2)`
+///
+/// The bound is conservative, and the actual cost of a query against a given document will
+/// typically be much lower.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CostBound {
+    degree: u32,
+}
+
+impl CostBound {
+    /// The exponent applied to the document size in the bound, i.e., `k` in `O(n^k)`
+    pub fn degree(&self) -> u32 {
+        self.degree
+    }
+
+    /// Whether or not the cost is independent of the size of the document, i.e., `O(1)`
+    pub fn is_constant(&self) -> bool {
+        self.degree == 0
+    }
+
+    /// Estimate the number of nodes visited for a document containing `document_size` nodes
+    ///
+    /// The result saturates at [`u128::MAX`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let cost = JsonPath::parse("$..foo..bar")?.analyze().cost();
+    /// assert_eq!(cost.estimate(1_000), 1_000_000);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn estimate(&self, document_size: usize) -> u128 {
+        (document_size as u128).saturating_pow(self.degree)
+    }
+}
+
+impl std::fmt::Display for CostBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.degree {
+            0 => write!(f, "O(1)"),
+            1 => write!(f, "O(n)"),
+            k => write!(f, "O(n^{k})"),
+        }
+    }
+}
+
+/// Check if `query` needs the location of the node being tested by a filter, in which case
+/// locations must be tracked while it is evaluated
+///
+/// This is the case if a filter calls a function that accepts a `FunctionContext`, which may use
+/// the location of the current node, e.g., `key` and `index`, or contains a relative query with a
+/// parent segment.
+pub(crate) fn uses_locations(query: &Query) -> bool {
+    let mut analyzer = Analyzer::default();
+    analyzer.subquery(query, 0);
+    analyzer.relative_parent_segments || analyzer.context_functions
+}
+
+/// Check if `expr` needs the location of the current node, in the same way as [`uses_locations`]
+pub(crate) fn function_uses_locations(expr: &FunctionExpr<Validated>) -> bool {
+    let mut analyzer = Analyzer::default();
+    analyzer.function_expr(expr, 0);
+    analyzer.relative_parent_segments || analyzer.context_functions
+}
+
+pub(crate) fn analyze(query: &Query) -> QueryAnalysis {
+    let mut analyzer = Analyzer::default();
+    let degree = analyzer.query(query, 0);
+    QueryAnalysis {
+        singular: query.is_singular(),
+        descendant_segments: analyzer.descendant_segments,
+        filter_depth: analyzer.filter_depth,
+        absolute_subqueries: analyzer.absolute_subqueries,
+        functions: analyzer.functions.into_iter().collect(),
+        cost: CostBound { degree },
+    }
+}
+
+/// Walks the query AST, accumulating the analysis as it goes
+///
+/// Each of the visiting methods returns the cost degree of the visited expression, following the
+/// model outlined on [`CostBound`]. The size of a nodelist is tracked in the same way: a nodelist
+/// of degree `d` contains at most `n^d` nodes.
+#[derive(Default)]
+struct Analyzer {
+    descendant_segments: usize,
+    filter_depth: usize,
+    absolute_subqueries: Vec<String>,
+    functions: BTreeSet<String>,
+    context_functions: bool,
+    relative_parent_segments: bool,
+}
+
+impl Analyzer {
+    fn query(&mut self, query: &Query, filter_depth: usize) -> u32 {
+        let mut size = 0;
+        let mut cost = 0;
+        for segment in &query.segments {
+            if segment.is_descendent() {
+                self.descendant_segments += 1;
+                // Visiting the descendants of each node in a nodelist may visit each node in the
+                // document once for each of its ancestors:
+                size += 1;
+                cost = cost.max(size);
+            }
+            let (out, c) = self.segment(&segment.segment, size, filter_depth);
+            size = out;
+            cost = cost.max(c);
+        }
+        cost
+    }
+
+    /// Returns the size degree of the nodelist produced by the segment, and the cost degree of
+    /// evaluating it against a nodelist of degree `size`
+    fn segment(&mut self, segment: &Segment, size: u32, filter_depth: usize) -> (u32, u32) {
+        match segment {
+            Segment::DotName(_) | Segment::Parent => (size, size),
+            Segment::Wildcard => (size.max(1), size.max(1)),
+            Segment::LongHand(selectors) => {
+                selectors
+                    .iter()
+                    .fold((size, size), |(out, cost), selector| {
+                        let (o, c) = self.selector(selector, size, filter_depth);
+                        (out.max(o), cost.max(c))
+                    })
+            }
+        }
+    }
+
+    fn selector(&mut self, selector: &Selector, size: u32, filter_depth: usize) -> (u32, u32) {
+        match selector {
+            Selector::Name(_) | Selector::Index(_) => (size, size),
+            Selector::Wildcard | Selector::ArraySlice(_) => (size.max(1), size.max(1)),
+            Selector::Filter(filter) => {
+                let candidates = size.max(1);
+                let cost = self.logical_expr(&filter.0, filter_depth + 1);
+                (candidates, candidates + cost)
+            }
+        }
+    }
+
+    fn logical_expr(&mut self, expr: &LogicalOrExpr, filter_depth: usize) -> u32 {
+        self.filter_depth = self.filter_depth.max(filter_depth);
+        expr.0
+            .iter()
+            .flat_map(|and| and.0.iter())
+            .map(|basic| self.basic_expr(basic, filter_depth))
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn basic_expr(&mut self, expr: &BasicExpr, filter_depth: usize) -> u32 {
+        match expr {
+            BasicExpr::Paren(e) | BasicExpr::NotParen(e) => self.logical_expr(e, filter_depth),
+            BasicExpr::Relation(cmp) => self
+                .comparable(&cmp.left, filter_depth)
+                .max(self.comparable(&cmp.right, filter_depth)),
+            BasicExpr::Exist(e) | BasicExpr::NotExist(e) => self.subquery(&e.0, filter_depth),
+            BasicExpr::FuncExpr(f) | BasicExpr::NotFuncExpr(f) => {
+                self.function_expr(f, filter_depth)
+            }
+            BasicExpr::Membership(m) => {
+                let value = self.comparable(&m.value, filter_depth);
+                match &m.array {
+                    ArrayOperand::List(_) => value,
+                    ArrayOperand::Comparable(c) => value.max(self.comparable(c, filter_depth)),
+                }
+            }
+        }
+    }
+
+    fn comparable(&mut self, comparable: &Comparable, filter_depth: usize) -> u32 {
+        match comparable {
+            Comparable::Literal(_) => 0,
+            Comparable::SingularQuery(q) => self.singular_query(q),
+            Comparable::FunctionExpr(f) => self.function_expr(f, filter_depth),
+            Comparable::Arithmetic(a) => self
+                .comparable(&a.left, filter_depth)
+                .max(self.comparable(&a.right, filter_depth)),
+        }
+    }
+
+    fn function_expr(&mut self, expr: &FunctionExpr<Validated>, filter_depth: usize) -> u32 {
+        self.functions.insert(expr.name.to_owned());
+        self.context_functions |= expr.validated.uses_context;
+        expr.args
+            .iter()
+            .map(|arg| match arg {
+                FunctionExprArg::Literal(_) => 0,
+                FunctionExprArg::SingularQuery(q) => self.singular_query(q),
+                FunctionExprArg::FilterQuery(q) => self.subquery(q, filter_depth),
+                FunctionExprArg::LogicalExpr(e) => self.logical_expr(e, filter_depth),
+                FunctionExprArg::FunctionExpr(f) => self.function_expr(f, filter_depth),
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn subquery(&mut self, query: &Query, filter_depth: usize) -> u32 {
+        match query.kind {
+            QueryKind::Root => self.absolute_subqueries.push(query.to_string()),
+            QueryKind::Current => self.relative_parent_segments |= query.has_parent_segments(),
+        }
+        self.query(query, filter_depth)
+    }
+
+    fn singular_query(&mut self, query: &SingularQuery) -> u32 {
+        if matches!(query.kind, SingularQueryKind::Absolute) {
+            self.absolute_subqueries.push(query.to_string());
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JsonPath;
+
+    #[test]
+    fn singular() {
+        for (q, exp) in [
+            ("$", true),
+            ("$.foo[0]['bar']", true),
+            ("$.foo[-1]", true),
+            ("$.foo.*", false),
+            ("$..foo", false),
+            ("$['foo','bar']", false),
+            ("$[?@.foo]", false),
+        ] {
+            let path = JsonPath::parse(q).expect("valid query");
+            assert_eq!(path.analyze().is_singular(), exp, "{q}");
+        }
+    }
+
+    #[test]
+    fn cost_degrees() {
+        for (q, exp) in [
+            ("$", 0),
+            ("$.foo.bar[0]", 0),
+            ("$.foo[*]", 1),
+            ("$.foo[*].bar[1:3]", 1),
+            ("$..foo", 1),
+            ("$..foo..bar", 2),
+            ("$[?@.a == $.b]", 1),
+            ("$[?count($..b) == 1]", 2),
+            ("$..[?@..b]", 2),
+            ("$[?@[?@[?@.a]]]", 3),
+            ("$[?count($..*) > 1]", 2),
+        ] {
+            let path = JsonPath::parse(q).expect("valid query");
+            assert_eq!(path.analyze().cost().degree(), exp, "{q}");
+        }
+    }
+
+    #[test]
+    fn filter_details() {
+        let path = JsonPath::parse(
+            "$..a[?@.b[?@.c > $.min] && (length(@.d) > 1 || count($..e) > 0 || value(@.f) == 1)]",
+        )
+        .expect("valid query");
+        let analysis = path.analyze();
+        assert_eq!(analysis.descendant_segments(), 2);
+        assert_eq!(analysis.filter_depth(), 2);
+        assert_eq!(analysis.absolute_subqueries(), ["$['min']", "$..e"]);
+        assert_eq!(analysis.functions(), ["count", "length", "value"]);
+    }
+
+    #[test]
+    fn cost_display_and_estimate() {
+        let path = JsonPath::parse("$..a..b..c").expect("valid query");
+        let cost = path.analyze().cost();
+        assert_eq!(cost.to_string(), "O(n^3)");
+        assert_eq!(cost.estimate(10), 1_000);
+        assert_eq!(cost.estimate(usize::MAX), u128::MAX);
+        assert!(JsonPath::parse("$.a")
+            .expect("valid query")
+            .analyze()
+            .cost()
+            .is_constant());
+    }
+}
@@ -0,0 +1,406 @@
+//! Structural differences between JSON values
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use serde_json::Value;
+use serde_json_path_core::path::{NormalizedPath, PathElement};
+
+use crate::JsonPath;
+
+/// A single difference between two [`serde_json::Value`]s
+///
+/// This is produced by the [`diff`] and [`diff_with`] functions.
+///
+/// When serialized, a change is represented as an object with a `type` of `"added"`,
+/// `"removed"` or `"modified"`, along with its fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Change<'a> {
+    /// A node that is only in the new value
+    Added {
+        /// The location of the node in the new value
+        path: NormalizedPath<'a>,
+        /// The added node
+        value: &'a Value,
+    },
+    /// A node that is only in the old value
+    Removed {
+        /// The location of the node in the old value
+        path: NormalizedPath<'a>,
+        /// The removed node
+        value: &'a Value,
+    },
+    /// A node that is in both values, but differs between them
+    ///
+    /// Objects and arrays that are in both values are compared member by member, or element by
+    /// element, so a modified node is either a primitive value, or a value that changed type.
+    Modified {
+        /// The location of the node in the new value
+        path: NormalizedPath<'a>,
+        /// The node in the old value
+        old: &'a Value,
+        /// The node in the new value
+        new: &'a Value,
+    },
+}
+
+impl<'a> Change<'a> {
+    /// Get the location of the changed node
+    ///
+    /// For removed nodes, this is the location in the old value; otherwise, it is the location
+    /// in the new value.
+    pub fn path(&self) -> &NormalizedPath<'a> {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Modified { path, .. } => path,
+        }
+    }
+}
+
+/// How the elements of arrays are paired up when computing a [`diff_with`]
+#[derive(Debug, Clone, Default)]
+pub enum ArrayDiff {
+    /// Compare the elements at the same index
+    #[default]
+    ByIndex,
+    /// Compare the elements that have the same key
+    ///
+    /// The key of each element is found by querying it with the given, typically relative,
+    /// [`JsonPath`], e.g., `@.id`, which must produce exactly one node. Elements whose key is not
+    /// in the other array are reported as added or removed.
+    ///
+    /// Arrays in which any element does not have exactly one key, or in which a key is used more
+    /// than once, are compared by index.
+    ByKey(JsonPath),
+}
+
+/// Options for computing a [`diff_with`]
+///
+/// # Example
+/// ```rust
+/// # use serde_json_path::{ArrayDiff, DiffOptions, JsonPath};
+/// # fn main() -> Result<(), serde_json_path::ParseError> {
+/// let options = DiffOptions::new()
+///     .arrays(ArrayDiff::ByKey(JsonPath::parse_relative("@.id")?))
+///     .ignore(JsonPath::parse("$..updated_at")?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    arrays: ArrayDiff,
+    ignore: Vec<JsonPath>,
+}
+
+impl DiffOptions {
+    /// Create the default options, which compare arrays by index and do not ignore any nodes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how the elements of arrays are compared
+    pub fn arrays(mut self, arrays: ArrayDiff) -> Self {
+        self.arrays = arrays;
+        self
+    }
+
+    /// Ignore any changes to the nodes matched by `path`, or their descendants
+    ///
+    /// The query is evaluated against both the old and the new value, and a node is ignored if
+    /// it is matched in either of them.
+    pub fn ignore(mut self, path: JsonPath) -> Self {
+        self.ignore.push(path);
+        self
+    }
+}
+
+/// Compute the structural differences between two [`serde_json::Value`]s
+///
+/// This uses the default [`DiffOptions`]; see [`diff_with`] for details.
+///
+/// # Example
+/// ```rust
+/// # use serde_json::json;
+/// # use serde_json_path::{diff, Change};
+/// let old = json!({"name": "api", "replicas": 2, "tags": ["a"]});
+/// let new = json!({"name": "api", "replicas": 3, "tags": ["a", "b"]});
+/// let changes: Vec<String> = diff(&old, &new)
+///     .iter()
+///     .map(|c| match c {
+///         Change::Added { path, value } => format!("+ {path}: {value}"),
+///         Change::Removed { path, value } => format!("- {path}: {value}"),
+///         Change::Modified { path, old, new } => format!("~ {path}: {old} -> {new}"),
+///     })
+///     .collect();
+/// assert_eq!(changes, ["~ $['replicas']: 2 -> 3", "+ $['tags'][1]: \"b\""]);
+/// ```
+pub fn diff<'a>(old: &'a Value, new: &'a Value) -> Vec<Change<'a>> {
+    diff_with(old, new, &DiffOptions::default())
+}
+
+/// Compute the structural differences between two [`serde_json::Value`]s using the given
+/// [`DiffOptions`]
+///
+/// Objects are compared member by member, and arrays element by element, recursively, so that
+/// each [`Change`] is reported at the most specific location possible. Changes are produced in
+/// the order that the nodes are visited: for each object or array, changed nodes from the old
+/// value come first, followed by those that were added in the new value.
+///
+/// # Example
+/// ```rust
+/// # use serde_json::json;
+/// # use serde_json_path::{diff_with, ArrayDiff, DiffOptions, JsonPath};
+/// # fn main() -> Result<(), serde_json_path::ParseError> {
+/// let old = json!({"users": [{"id": 1, "name": "alice"}, {"id": 2, "name": "bob"}]});
+/// let new = json!({"users": [{"id": 2, "name": "robert"}]});
+/// let options = DiffOptions::new().arrays(ArrayDiff::ByKey(JsonPath::parse_relative("@.id")?));
+/// let changes: Vec<String> = diff_with(&old, &new, &options)
+///     .iter()
+///     .map(|c| c.path().to_string())
+///     .collect();
+/// assert_eq!(changes, ["$['users'][0]", "$['users'][0]['name']"]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn diff_with<'a>(old: &'a Value, new: &'a Value, options: &DiffOptions) -> Vec<Change<'a>> {
+    let ignored = |value: &'a Value| -> HashSet<NormalizedPath<'a>> {
+        options
+            .ignore
+            .iter()
+            .flat_map(|path| {
+                path.query_located(value)
+                    .into_iter()
+                    .map(NormalizedPath::from)
+            })
+            .collect()
+    };
+    let mut differ = Differ {
+        arrays: &options.arrays,
+        ignored_old: ignored(old),
+        ignored_new: ignored(new),
+        changes: Vec::new(),
+    };
+    differ.node(
+        old,
+        new,
+        NormalizedPath::default(),
+        NormalizedPath::default(),
+    );
+    differ.changes
+}
+
+struct Differ<'a, 'o> {
+    arrays: &'o ArrayDiff,
+    ignored_old: HashSet<NormalizedPath<'a>>,
+    ignored_new: HashSet<NormalizedPath<'a>>,
+    changes: Vec<Change<'a>>,
+}
+
+impl<'a> Differ<'a, '_> {
+    fn node(
+        &mut self,
+        old: &'a Value,
+        new: &'a Value,
+        old_path: NormalizedPath<'a>,
+        new_path: NormalizedPath<'a>,
+    ) {
+        if self.ignored_old.contains(&old_path) || self.ignored_new.contains(&new_path) {
+            return;
+        }
+        match (old, new) {
+            (Value::Object(old_map), Value::Object(new_map)) => {
+                for (k, old_v) in old_map {
+                    match new_map.get(k) {
+                        Some(new_v) => {
+                            self.node(old_v, new_v, child(&old_path, k), child(&new_path, k))
+                        }
+                        None => self.removed(old_v, child(&old_path, k)),
+                    }
+                }
+                for (k, new_v) in new_map {
+                    if !old_map.contains_key(k) {
+                        self.added(new_v, child(&new_path, k));
+                    }
+                }
+            }
+            (Value::Array(old_list), Value::Array(new_list)) => {
+                self.array(old_list, new_list, old_path, new_path)
+            }
+            (old, new) if old != new => self.changes.push(Change::Modified {
+                path: new_path,
+                old,
+                new,
+            }),
+            _ => (),
+        }
+    }
+
+    fn array(
+        &mut self,
+        old: &'a [Value],
+        new: &'a [Value],
+        old_path: NormalizedPath<'a>,
+        new_path: NormalizedPath<'a>,
+    ) {
+        let keyed = match self.arrays {
+            ArrayDiff::ByIndex => None,
+            ArrayDiff::ByKey(key) => keys(key, old).zip(keys(key, new)),
+        };
+        let Some((old_keys, new_keys)) = keyed else {
+            for (i, old_v) in old.iter().enumerate() {
+                match new.get(i) {
+                    Some(new_v) => {
+                        self.node(old_v, new_v, child(&old_path, i), child(&new_path, i))
+                    }
+                    None => self.removed(old_v, child(&old_path, i)),
+                }
+            }
+            for (i, new_v) in new.iter().enumerate().skip(old.len()) {
+                self.added(new_v, child(&new_path, i));
+            }
+            return;
+        };
+        let new_indices: HashMap<&String, usize> =
+            new_keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+        for (i, (old_v, k)) in old.iter().zip(&old_keys).enumerate() {
+            match new_indices.get(k) {
+                Some(&j) => self.node(old_v, &new[j], child(&old_path, i), child(&new_path, j)),
+                None => self.removed(old_v, child(&old_path, i)),
+            }
+        }
+        let old_keys: HashSet<&String> = old_keys.iter().collect();
+        for (j, (new_v, k)) in new.iter().zip(&new_keys).enumerate() {
+            if !old_keys.contains(k) {
+                self.added(new_v, child(&new_path, j));
+            }
+        }
+    }
+
+    fn added(&mut self, value: &'a Value, path: NormalizedPath<'a>) {
+        if !self.ignored_new.contains(&path) {
+            self.changes.push(Change::Added { path, value });
+        }
+    }
+
+    fn removed(&mut self, value: &'a Value, path: NormalizedPath<'a>) {
+        if !self.ignored_old.contains(&path) {
+            self.changes.push(Change::Removed { path, value });
+        }
+    }
+}
+
+/// Get the keys of each element in `list`, or `None` if any element does not have exactly one key,
+/// or if any key is used more than once
+///
+/// Keys are compared using their JSON representation.
+fn keys(key: &JsonPath, list: &[Value]) -> Option<Vec<String>> {
+    let keys = list
+        .iter()
+        .map(|v| key.query(v).exactly_one().ok().map(Value::to_string))
+        .collect::<Option<Vec<String>>>()?;
+    let unique: HashSet<&String> = keys.iter().collect();
+    (unique.len() == keys.len()).then_some(keys)
+}
+
+/// Produce the location of the child of the node at `path`
+fn child<'a>(path: &NormalizedPath<'a>, element: impl Into<PathElement<'a>>) -> NormalizedPath<'a> {
+    path.join(&NormalizedPath::from(vec![element.into()]))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{diff, diff_with, ArrayDiff, Change, DiffOptions};
+    use crate::JsonPath;
+
+    fn summary(changes: &[Change<'_>]) -> Vec<String> {
+        changes
+            .iter()
+            .map(|c| match c {
+                Change::Added { path, value } => format!("+ {path} {value}"),
+                Change::Removed { path, value } => format!("- {path} {value}"),
+                Change::Modified { path, old, new } => format!("~ {path} {old} {new}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diff_by_index() {
+        let old = json!({"a": 1, "b": [1, 2, 3], "c": {"d": true}, "e": "x"});
+        let new = json!({"a": 1, "b": [1, 5], "c": [true], "f": null});
+        assert_eq!(
+            summary(&diff(&old, &new)),
+            [
+                "~ $['b'][1] 2 5",
+                "- $['b'][2] 3",
+                "~ $['c'] {\"d\":true} [true]",
+                "- $['e'] \"x\"",
+                "+ $['f'] null",
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn diff_by_key() {
+        let old = json!([{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 3, "v": "c"}]);
+        let new = json!([{"id": 3, "v": "c"}, {"id": 4, "v": "d"}, {"id": 1, "v": "z"}]);
+        let options =
+            DiffOptions::new().arrays(ArrayDiff::ByKey(JsonPath::parse_relative("@.id").unwrap()));
+        assert_eq!(
+            summary(&diff_with(&old, &new, &options)),
+            [
+                "~ $[2]['v'] \"a\" \"z\"",
+                "- $[1] {\"id\":2,\"v\":\"b\"}",
+                "+ $[1] {\"id\":4,\"v\":\"d\"}",
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_by_key_falls_back_to_index() {
+        let options =
+            DiffOptions::new().arrays(ArrayDiff::ByKey(JsonPath::parse_relative("@.id").unwrap()));
+        for (old, new, exp) in [
+            (
+                json!(["a", "b"]),
+                json!(["b"]),
+                vec!["~ $[0] \"a\" \"b\"", "- $[1] \"b\""],
+            ),
+            (
+                json!([{"id": 1}, {"id": 1, "v": 0}]),
+                json!([{"id": 1, "v": 0}]),
+                vec!["+ $[0]['v'] 0", "- $[1] {\"id\":1,\"v\":0}"],
+            ),
+        ] {
+            assert_eq!(summary(&diff_with(&old, &new, &options)), exp);
+        }
+    }
+
+    #[test]
+    fn diff_ignore() {
+        let old = json!({"meta": {"updated": 1, "by": "a"}, "items": [{"t": 1}, {"t": 2}]});
+        let new = json!({"meta": {"updated": 2, "by": "b"}, "items": [{"t": 3}], "extra": 1});
+        let options = DiffOptions::new()
+            .ignore(JsonPath::parse("$.meta.updated").unwrap())
+            .ignore(JsonPath::parse("$..t").unwrap())
+            .ignore(JsonPath::parse("$.extra").unwrap());
+        assert_eq!(
+            summary(&diff_with(&old, &new, &options)),
+            ["- $['items'][1] {\"t\":2}", "~ $['meta']['by'] \"a\" \"b\""]
+        );
+    }
+
+    #[test]
+    fn serialize_changes() {
+        let old = json!({"a": 1});
+        let new = json!({"a": 2});
+        let changes = serde_json::to_value(diff(&old, &new)).unwrap();
+        assert_eq!(
+            changes,
+            json!([{"type": "modified", "path": "$['a']", "old": 1, "new": 2}])
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::ops::Deref;
 
+use serde_json_path_core::path::NormalizedPath;
+
 use crate::parser::Error;
 
 /// Error type for JSONPath query string parsing errors
@@ -10,6 +12,16 @@ pub struct ParseError {
 }
 
 impl ParseError {
+    #[cfg(feature = "legacy")]
+    pub(crate) fn new(position: usize, message: impl Into<Box<str>>) -> Self {
+        Self {
+            err: Box::new(ErrorImpl {
+                position,
+                message: message.into(),
+            }),
+        }
+    }
+
     /// Get the 1-indexed error position
     pub fn position(&self) -> usize {
         self.err.position
@@ -43,6 +55,158 @@ where
     }
 }
 
+/// Error type for invalid [JSON Pointer][json-pointer] strings
+///
+/// This is produced by the [`JsonPath::from_json_pointer`][crate::JsonPath::from_json_pointer]
+/// method.
+///
+/// [json-pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub enum JsonPointerError {
+    /// A non-empty JSON Pointer did not start with a `/`
+    #[error("a non-empty JSON Pointer must start with '/'")]
+    MissingLeadingSlash,
+    /// A `~` in the JSON Pointer was not followed by a `0` or `1`
+    #[error("at position {position}, '~' must be followed by '0' or '1'")]
+    InvalidEscape {
+        /// The 1-indexed position of the `~` in the JSON Pointer
+        position: usize,
+    },
+}
+
+/// Error produced when rebuilding a [`serde_json::Value`] from a set of leaf nodes fails
+///
+/// This is produced by the [`unflatten`][crate::unflatten] function.
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+#[error("{kind} at {path}")]
+pub struct UnflattenError {
+    path: NormalizedPath<'static>,
+    kind: UnflattenErrorKind,
+}
+
+impl UnflattenError {
+    pub(crate) fn new(path: NormalizedPath<'static>, kind: UnflattenErrorKind) -> Self {
+        Self { path, kind }
+    }
+
+    /// Get the location of the node at which the error occurred
+    pub fn path(&self) -> &NormalizedPath<'static> {
+        &self.path
+    }
+
+    /// Get the reason that the value could not be rebuilt
+    pub fn kind(&self) -> &UnflattenErrorKind {
+        &self.kind
+    }
+}
+
+/// The reason that a [`serde_json::Value`] could not be rebuilt from a set of leaf nodes
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum UnflattenErrorKind {
+    /// Two leaf nodes have the same location, one is located within the other, or they use
+    /// different kinds of [`PathElement`][crate::PathElement] for the same node
+    #[error("conflicting leaf nodes")]
+    Conflict,
+    /// An index is beyond the end of an array, which would leave a gap in the array
+    #[error("index {index} is beyond the end of the array of length {len}")]
+    IndexOutOfRange {
+        /// The index of the leaf node's location
+        index: usize,
+        /// The length of the array when the leaf node was added
+        len: usize,
+    },
+}
+
+/// Error produced when a column of a [`TableExtractor`][crate::TableExtractor] that uses
+/// [`Cardinality::AtMostOne`][crate::Cardinality::AtMostOne] matches more than one node
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+#[error("column '{column}' matched {count} nodes in the row at {row}, but expected at most one")]
+pub struct TableError {
+    row: NormalizedPath<'static>,
+    column: String,
+    count: usize,
+}
+
+impl TableError {
+    pub(crate) fn new(row: NormalizedPath<'static>, column: String, count: usize) -> Self {
+        Self { row, column, count }
+    }
+
+    /// Get the location of the node that produced the row
+    pub fn row(&self) -> &NormalizedPath<'static> {
+        &self.row
+    }
+
+    /// Get the name of the column
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    /// Get the number of nodes that were matched
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Error produced when applying a JSON Patch fails
+///
+/// This is produced by the [`apply_patch`][crate::apply_patch] function.
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+#[error("patch operation {operation} failed: {kind}")]
+pub struct PatchError {
+    operation: usize,
+    kind: PatchErrorKind,
+}
+
+impl PatchError {
+    pub(crate) fn new(operation: usize, kind: PatchErrorKind) -> Self {
+        Self { operation, kind }
+    }
+
+    /// Get the 0-indexed position, within the patch, of the operation that failed
+    pub fn operation(&self) -> usize {
+        self.operation
+    }
+
+    /// Get the reason that the operation failed
+    pub fn kind(&self) -> &PatchErrorKind {
+        &self.kind
+    }
+}
+
+/// The reason that a JSON Patch operation failed
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum PatchErrorKind {
+    /// A `path` or `from` of the operation was not a valid JSON Pointer
+    #[error(transparent)]
+    InvalidPointer(#[from] JsonPointerError),
+    /// The location does not exist in the value
+    #[error("the location {path} does not exist")]
+    NotFound {
+        /// The JSON Pointer of the location
+        path: String,
+    },
+    /// A `test` operation found a value that was not equal to the expected value
+    #[error("the value at {path} is not equal to the expected value")]
+    TestFailed {
+        /// The JSON Pointer of the tested location
+        path: String,
+    },
+    /// A `move` operation attempted to move a node into one of its descendants
+    #[error("can not move {from} into its descendant {path}")]
+    MoveIntoDescendant {
+        /// The JSON Pointer of the node being moved
+        from: String,
+        /// The JSON Pointer of the target location
+        path: String,
+    },
+    /// A `remove` or `move` operation attempted to remove the root node
+    #[error("the root node can not be removed")]
+    RemoveRoot,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::ParseError;
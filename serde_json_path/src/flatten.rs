@@ -0,0 +1,270 @@
+//! Flattening of JSON values into their leaf nodes, and back again
+use std::collections::HashSet;
+
+use serde_json::{Map, Value};
+use serde_json_path_core::path::{NormalizedPath, PathElement};
+
+use crate::{UnflattenError, UnflattenErrorKind};
+
+/// Flatten a [`serde_json::Value`] into the locations and values of its leaf nodes
+///
+/// The leaf nodes are the primitive values, i.e., strings, numbers, booleans and `null`, along
+/// with any empty objects or arrays. They are produced in document order, and the locations do
+/// not borrow from `value`, so they can be stored independently of it.
+///
+/// The original value can be rebuilt from the leaf nodes using [`unflatten`].
+///
+/// # Example
+/// ```rust
+/// # use serde_json::json;
+/// # use serde_json_path::flatten;
+/// let value = json!({"foo": [1, {"bar": true}], "baz": {}});
+/// let leaves: Vec<(String, _)> = flatten(&value)
+///     .into_iter()
+///     .map(|(path, node)| (path.to_string(), node))
+///     .collect();
+/// assert_eq!(leaves, [
+///     ("$['baz']".to_owned(), json!({})),
+///     ("$['foo'][0]".to_owned(), json!(1)),
+///     ("$['foo'][1]['bar']".to_owned(), json!(true)),
+/// ]);
+/// ```
+pub fn flatten(value: &Value) -> Vec<(NormalizedPath<'static>, Value)> {
+    let mut leaves = Vec::new();
+    flatten_into(value, &mut Vec::new(), &mut leaves);
+    leaves
+}
+
+fn flatten_into(
+    value: &Value,
+    path: &mut Vec<PathElement<'static>>,
+    leaves: &mut Vec<(NormalizedPath<'static>, Value)>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                path.push(PathElement::from(k.to_owned()));
+                flatten_into(v, path, leaves);
+                path.pop();
+            }
+        }
+        Value::Array(list) if !list.is_empty() => {
+            for (i, v) in list.iter().enumerate() {
+                path.push(PathElement::Index(i));
+                flatten_into(v, path, leaves);
+                path.pop();
+            }
+        }
+        leaf => leaves.push((NormalizedPath::from(path.clone()), leaf.clone())),
+    }
+}
+
+/// Rebuild a [`serde_json::Value`] from the locations and values of its leaf nodes
+///
+/// Objects and arrays are created as needed to hold each leaf node, based on the kind of each
+/// [`PathElement`] in its location: names produce objects, and indices produce arrays. An empty
+/// set of leaves produces `null`.
+///
+/// This is the inverse of [`flatten`], and so the elements of each array must be given in order:
+/// an error is produced if an index is beyond the end of its array, rather than filling the gap.
+/// An error is also produced if two leaves conflict with each other, i.e., they have the same
+/// location, one is located within the other, or they use different kinds of [`PathElement`] for
+/// the same node. Conflicts are detected regardless of the order in which the leaves are given.
+///
+/// # Example
+/// ```rust
+/// # use serde_json::json;
+/// # use serde_json_path::{flatten, unflatten, NormalizedPath};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let leaves = [
+///     ("$['foo'][0]".parse::<NormalizedPath>()?, json!(null)),
+///     ("$['foo'][1]".parse()?, json!("bar")),
+///     ("$['baz']['bop']".parse()?, json!(true)),
+/// ];
+/// let value = unflatten(leaves)?;
+/// assert_eq!(value, json!({"foo": [null, "bar"], "baz": {"bop": true}}));
+/// assert_eq!(unflatten(flatten(&value))?, value);
+/// # Ok(())
+/// # }
+/// ```
+pub fn unflatten<'p, I>(leaves: I) -> Result<Value, UnflattenError>
+where
+    I: IntoIterator<Item = (NormalizedPath<'p>, Value)>,
+{
+    let mut root: Option<Value> = None;
+    // the locations of leaves that are non-empty objects or arrays, which can not otherwise be
+    // told apart from the nodes that were created to hold other leaves:
+    let mut branch_leaves = HashSet::new();
+    for (path, leaf) in leaves {
+        // a node is vacant if it was created to hold this leaf:
+        let mut vacant = root.is_none();
+        let mut node = root.get_or_insert(Value::Null);
+        for (i, element) in path.iter().enumerate() {
+            if vacant {
+                *node = match element {
+                    PathElement::Name(_) => Value::Object(Map::new()),
+                    PathElement::Index(_) => Value::Array(Vec::new()),
+                };
+            } else if !is_branch(node) || branch_leaves.contains(&prefix(&path, i)) {
+                return Err(conflict(&path, i));
+            }
+            node = match (element, node) {
+                (PathElement::Name(name), Value::Object(map)) => {
+                    vacant = !map.contains_key(name.as_ref());
+                    map.entry(name.as_ref()).or_insert(Value::Null)
+                }
+                (PathElement::Index(index), Value::Array(list)) => {
+                    vacant = *index == list.len();
+                    if vacant {
+                        list.push(Value::Null);
+                    } else if *index > list.len() {
+                        return Err(UnflattenError::new(
+                            prefix(&path, i),
+                            UnflattenErrorKind::IndexOutOfRange {
+                                index: *index,
+                                len: list.len(),
+                            },
+                        ));
+                    }
+                    &mut list[*index]
+                }
+                _ => return Err(conflict(&path, i)),
+            };
+        }
+        if !vacant {
+            return Err(conflict(&path, path.len()));
+        }
+        if is_branch(&leaf) {
+            branch_leaves.insert(prefix(&path, path.len()));
+        }
+        *node = leaf;
+    }
+    Ok(root.unwrap_or_default())
+}
+
+/// Check if a node is a non-empty object or array, i.e., is not a leaf node
+fn is_branch(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(list) => !list.is_empty(),
+        _ => false,
+    }
+}
+
+/// The location of the node located by the first `len` elements of `path`
+fn prefix(path: &NormalizedPath<'_>, len: usize) -> NormalizedPath<'static> {
+    path.iter()
+        .take(len)
+        .cloned()
+        .map(PathElement::into_owned)
+        .collect()
+}
+
+/// Produce an error for a conflict at the node located by the first `len` elements of `path`
+fn conflict(path: &NormalizedPath<'_>, len: usize) -> UnflattenError {
+    UnflattenError::new(prefix(path, len), UnflattenErrorKind::Conflict)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use serde_json_path_core::path::NormalizedPath;
+
+    use crate::UnflattenErrorKind;
+
+    use super::{flatten, unflatten};
+
+    #[test]
+    fn round_trip() {
+        for value in [
+            json!(null),
+            json!("foo"),
+            json!({}),
+            json!([]),
+            json!({"a": [1, [], {}, {"b": null}], "c": {"d": "e", "f": [[true]]}}),
+        ] {
+            assert_eq!(unflatten(flatten(&value)).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn flatten_leaves() {
+        let value = json!([{"a": []}, null, [1]]);
+        let leaves: Vec<String> = flatten(&value)
+            .into_iter()
+            .map(|(path, node)| format!("{path} = {node}"))
+            .collect();
+        assert_eq!(leaves, ["$[0]['a'] = []", "$[1] = null", "$[2][0] = 1"]);
+    }
+
+    #[test]
+    fn unflatten_conflicts() {
+        for (leaves, conflict) in [
+            (
+                vec![("$['a']", json!(1)), ("$['a']['b']", json!(2))],
+                "$['a']",
+            ),
+            (
+                vec![("$['a']['b']", json!(1)), ("$['a']", json!(2))],
+                "$['a']",
+            ),
+            (
+                vec![("$['a'][0]", json!(1)), ("$['a']['b']", json!(2))],
+                "$['a']",
+            ),
+            (vec![("$[0]", json!(1)), ("$['a']", json!(2))], "$"),
+        ] {
+            let leaves = leaves
+                .into_iter()
+                .map(|(p, v)| (p.parse::<NormalizedPath>().unwrap(), v));
+            let err = unflatten(leaves).unwrap_err();
+            assert_eq!(err.path().to_string(), conflict);
+        }
+    }
+
+    #[test]
+    fn unflatten_conflicts_regardless_of_order() {
+        for (first, second, conflict) in [
+            (("$['a']", json!(null)), ("$['a']['b']", json!(1)), "$['a']"),
+            (("$['a']", json!({})), ("$['a']['b']", json!(1)), "$['a']"),
+            (("$['a']", json!([])), ("$['a'][0]", json!(1)), "$['a']"),
+            (
+                ("$['a']", json!({"b": 1})),
+                ("$['a']['c']", json!(1)),
+                "$['a']",
+            ),
+            (("$['c']", json!(1)), ("$['c']", json!(2)), "$['c']"),
+            (("$", json!(null)), ("$[0]", json!(1)), "$"),
+        ] {
+            for leaves in [[first.clone(), second.clone()], [second, first]] {
+                let leaves = leaves
+                    .into_iter()
+                    .map(|(p, v)| (p.parse::<NormalizedPath>().unwrap(), v));
+                let err = unflatten(leaves).unwrap_err();
+                assert_eq!(err.kind(), &UnflattenErrorKind::Conflict);
+                assert_eq!(err.path().to_string(), conflict);
+            }
+        }
+    }
+
+    #[test]
+    fn unflatten_index_out_of_range() {
+        let leaves = [
+            ("$['a'][0]", json!(1)),
+            ("$['a'][9007199254740991]", json!(2)),
+        ]
+        .into_iter()
+        .map(|(p, v)| (p.parse::<NormalizedPath>().unwrap(), v));
+        let err = unflatten(leaves).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &UnflattenErrorKind::IndexOutOfRange {
+                index: 9007199254740991,
+                len: 1
+            }
+        );
+        assert_eq!(err.path().to_string(), "$['a']");
+        let leaves = [("$[1]".parse::<NormalizedPath>().unwrap(), json!(1))];
+        assert!(unflatten(leaves).is_err());
+    }
+}
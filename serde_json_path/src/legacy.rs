@@ -0,0 +1,549 @@
+//! Conversion of queries written in the legacy JSONPath syntax that predates RFC 9535
+//!
+//! The legacy syntax is that of the original JSONPath proposal by Stefan Goessner, along with the
+//! extensions made to it by implementations such as Jayway. Much of it is valid RFC 9535 syntax,
+//! so a legacy query is converted by rewriting the parts that are not into their RFC 9535
+//! equivalents, and parsing the result. Positions in any error refer to the legacy query.
+use serde_json_path_core::spec::query::Query;
+
+use crate::{parser::parse_query_main, ParseError};
+
+/// Parse a legacy JSONPath query, converting it to an RFC 9535 [`Query`]
+pub(crate) fn parse_legacy_query(input: &str) -> Result<Query, ParseError> {
+    let mut translator = Translator {
+        src: input,
+        pos: 0,
+        out: String::with_capacity(input.len()),
+        origins: Vec::with_capacity(input.len()),
+    };
+    translator.query()?;
+    let (_, query) = parse_query_main(&translator.out).map_err(|err| match err {
+        nom::Err::Error(e) | nom::Err::Failure(e) => {
+            let err = ParseError::from((translator.out.as_str(), e));
+            ParseError::new(translator.origin(err.position()), err.message())
+        }
+        nom::Err::Incomplete(_) => unreachable!("we do not use streaming parsers"),
+    })?;
+    Ok(query)
+}
+
+/// Operators supported by Jayway that have no equivalent in RFC 9535
+const UNSUPPORTED_OPERATORS: [&str; 6] =
+    ["anyof", "noneof", "subsetof", "size", "empty", "contains"];
+
+/// Rewrites a legacy query as an RFC 9535 query
+///
+/// Each byte of the output is mapped to the position in the legacy query that it was produced
+/// from, so that errors in the output can be reported against the legacy query.
+struct Translator<'a> {
+    src: &'a str,
+    pos: usize,
+    out: String,
+    origins: Vec<usize>,
+}
+
+impl Translator<'_> {
+    fn query(&mut self) -> Result<(), ParseError> {
+        if matches!(self.peek(), Some('$' | '@')) {
+            self.copy(1);
+        }
+        self.segments()?;
+        // anything left over is invalid, which is left for the RFC 9535 parser to report:
+        self.copy(self.src.len() - self.pos);
+        Ok(())
+    }
+
+    fn segments(&mut self) -> Result<(), ParseError> {
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("..") {
+                self.copy(2);
+                match self.peek() {
+                    Some('[') => self.bracket()?,
+                    Some('*') => self.copy(1),
+                    _ => self.name(self.pos - 2, false)?,
+                }
+            } else if rest.starts_with(".*") {
+                self.copy(2);
+            } else if rest.starts_with('.') {
+                self.pos += 1;
+                self.name(self.pos - 1, true)?;
+            } else if rest.starts_with('[') {
+                self.bracket()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Rewrite a member name following a `.` or `..`, which is written in long-hand form if it is
+    /// not a valid RFC 9535 member name, e.g., `.2024` is written as `['2024']`
+    fn name(&mut self, start: usize, dot: bool) -> Result<(), ParseError> {
+        let len = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || ".[]()'\"=!<>&|,~^".contains(c))
+            .unwrap_or(self.rest().len());
+        let name = &self.src[self.pos..self.pos + len];
+        if self.rest()[len..].starts_with('(') {
+            return Err(ParseError::new(
+                start,
+                format!("functions at the end of a path, e.g., '.{name}()', have no RFC 9535 equivalent"),
+            ));
+        }
+        let mut chars = name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || !c.is_ascii())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii());
+        if valid || name.is_empty() {
+            if dot {
+                self.emit(".", start);
+            }
+            self.copy(len);
+        } else {
+            let escaped = name.replace('\\', "\\\\").replace('\'', "\\'");
+            self.emit(&format!("['{escaped}']"), start);
+            self.pos += len;
+        }
+        Ok(())
+    }
+
+    fn bracket(&mut self) -> Result<(), ParseError> {
+        self.copy(1);
+        loop {
+            self.whitespace();
+            match self.peek() {
+                Some('?') => {
+                    self.copy(1);
+                    self.expression()?;
+                }
+                Some('(') => self.script()?,
+                _ => loop {
+                    match self.peek() {
+                        Some('\'' | '"') => self.copy(self.string_len()),
+                        Some(',' | ']') | None => break,
+                        Some(c) => self.copy(c.len_utf8()),
+                    }
+                },
+            }
+            match self.peek() {
+                Some(',') => self.copy(1),
+                Some(']') => {
+                    self.copy(1);
+                    return Ok(());
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Rewrite a script expression, which is only supported in the form `(@.length-N)`, selecting
+    /// the `N`th element from the end of an array
+    fn script(&mut self) -> Result<(), ParseError> {
+        let start = self.pos;
+        let offset = parse_length_offset(self.rest()).ok_or_else(|| {
+            ParseError::new(
+                start,
+                "script expressions have no RFC 9535 equivalent, except for '(@.length-N)'",
+            )
+        })?;
+        self.pos += offset.0;
+        self.emit(&format!("-{n}", n = offset.1), start);
+        Ok(())
+    }
+
+    /// Rewrite a filter expression, up to the end of the selector that contains it
+    fn expression(&mut self) -> Result<(), ParseError> {
+        // the position in the output of the start of the most recent operand, if it was directly
+        // followed by an operator:
+        let mut operand: Option<usize> = None;
+        // the start, in the output, of the function call that each open parenthesis belongs to:
+        let mut parens: Vec<Option<usize>> = Vec::new();
+        while let Some(c) = self.peek() {
+            match c {
+                ']' | ',' if parens.is_empty() => return Ok(()),
+                '(' => {
+                    parens.push(None);
+                    operand = None;
+                    self.copy(1);
+                }
+                ')' => {
+                    operand = parens.pop().flatten();
+                    self.copy(1);
+                }
+                '\'' | '"' => {
+                    operand = Some(self.out.len());
+                    self.copy(self.string_len());
+                }
+                '@' | '$' => {
+                    operand = Some(self.out.len());
+                    self.copy(1);
+                    self.segments()?;
+                }
+                '=' if self.rest().starts_with("=~") => {
+                    self.regex(operand.take())?;
+                }
+                c if c.is_ascii_digit()
+                    || (c == '-' && self.rest()[1..].starts_with(|c: char| c.is_ascii_digit())) =>
+                {
+                    operand = Some(self.out.len());
+                    self.copy(number_len(self.rest()));
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let start = self.pos;
+                    let len = self
+                        .rest()
+                        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .unwrap_or(self.rest().len());
+                    let word = &self.src[start..start + len];
+                    if self.rest()[len..].starts_with('(') {
+                        parens.push(Some(self.out.len()));
+                        self.copy(len + 1);
+                    } else if word == "in" || word == "nin" {
+                        self.pos += len;
+                        self.membership(operand.take(), start, word == "nin")?;
+                    } else if UNSUPPORTED_OPERATORS.contains(&word) {
+                        return Err(ParseError::new(
+                            start,
+                            format!("the '{word}' operator has no RFC 9535 equivalent"),
+                        ));
+                    } else {
+                        operand = Some(self.out.len());
+                        self.copy(len);
+                    }
+                }
+                c if c.is_whitespace() => self.copy(c.len_utf8()),
+                c => {
+                    operand = None;
+                    self.copy(c.len_utf8());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrite `left =~ /pattern/flags` as `match(left, 'pattern')`
+    fn regex(&mut self, operand: Option<usize>) -> Result<(), ParseError> {
+        let start = self.pos;
+        let (left, left_origins) = self.take_operand(operand, start, "=~")?;
+        self.pos += 2;
+        self.skip_whitespace();
+        let literal = self.pos;
+        if self.peek() != Some('/') {
+            return Err(ParseError::new(
+                literal,
+                "expected a regular expression, e.g., /pattern/, after '=~'",
+            ));
+        }
+        self.pos += 1;
+        let mut pattern = String::new();
+        loop {
+            let mut chars = self.rest().chars();
+            match (chars.next(), chars.next()) {
+                (None, _) => {
+                    return Err(ParseError::new(literal, "unterminated regular expression"));
+                }
+                (Some('/'), _) => break,
+                (Some('\\'), Some('/')) => {
+                    pattern.push('/');
+                    self.pos += 2;
+                }
+                (Some('\\'), Some(c)) => {
+                    pattern.push('\\');
+                    pattern.push(c);
+                    self.pos += 1 + c.len_utf8();
+                }
+                (Some(c), _) => {
+                    pattern.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+        self.pos += 1;
+        while let Some(flag) = self.peek().filter(char::is_ascii_alphabetic) {
+            match flag {
+                'i' => pattern.insert_str(0, "(?i)"),
+                _ => {
+                    return Err(ParseError::new(
+                        self.pos,
+                        format!("the regular expression flag '{flag}' has no RFC 9535 equivalent"),
+                    ))
+                }
+            }
+            self.pos += 1;
+        }
+        let pattern = pattern.replace('\\', "\\\\").replace('\'', "\\'");
+        self.emit("match(", start);
+        self.emit_from(&left, &left_origins);
+        self.emit(&format!(", '{pattern}')"), literal);
+        Ok(())
+    }
+
+    /// Rewrite `left in [a, b]` as `(left == a || left == b)`, and `left nin [a, b]` as
+    /// `(left != a && left != b)`
+    fn membership(
+        &mut self,
+        operand: Option<usize>,
+        start: usize,
+        negated: bool,
+    ) -> Result<(), ParseError> {
+        let operator = if negated { "nin" } else { "in" };
+        let (left, left_origins) = self.take_operand(operand, start, operator)?;
+        self.skip_whitespace();
+        let list = self.pos;
+        if self.peek() != Some('[') {
+            return Err(ParseError::new(
+                list,
+                format!("expected a list of literal values, e.g., ['a', 'b'], after '{operator}'"),
+            ));
+        }
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if items.is_empty() && self.peek() == Some(']') {
+                self.pos += 1;
+                break;
+            }
+            let rest = self.rest();
+            let len = match rest.chars().next() {
+                Some('\'' | '"') => self.string_len(),
+                Some(c) if c.is_ascii_digit() || c == '-' => number_len(rest),
+                _ => ["true", "false", "null"]
+                    .into_iter()
+                    .find(|l| rest.starts_with(l))
+                    .map_or(0, str::len),
+            };
+            if len == 0 {
+                return Err(ParseError::new(
+                    self.pos,
+                    "expected a literal value in the list",
+                ));
+            }
+            items.push(self.pos..self.pos + len);
+            self.pos += len;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(ParseError::new(self.pos, "expected ',' or ']' in the list")),
+            }
+        }
+        if items.is_empty() {
+            return Err(ParseError::new(
+                list,
+                "an empty list has no RFC 9535 equivalent",
+            ));
+        }
+        let (comparison, join) = if negated {
+            (" != ", " && ")
+        } else {
+            (" == ", " || ")
+        };
+        self.emit("(", start);
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.emit(join, start);
+            }
+            self.emit_from(&left, &left_origins);
+            self.emit(comparison, start);
+            let origins: Vec<usize> = item.clone().collect();
+            self.emit_from(&self.src[item], &origins);
+        }
+        self.emit(")", start);
+        Ok(())
+    }
+
+    /// Remove the operand to the left of an operator from the output, returning it along with
+    /// the positions it was produced from
+    fn take_operand(
+        &mut self,
+        operand: Option<usize>,
+        operator_position: usize,
+        operator: &str,
+    ) -> Result<(String, Vec<usize>), ParseError> {
+        let start = operand.ok_or_else(|| {
+            ParseError::new(
+                operator_position,
+                format!("expected a value before '{operator}'"),
+            )
+        })?;
+        let left = self.out[start..].trim_end().to_owned();
+        let origins = self.origins[start..start + left.len()].to_vec();
+        self.out.truncate(start);
+        self.origins.truncate(start);
+        Ok((left, origins))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    /// Copy `len` bytes of the legacy query to the output, unchanged
+    fn copy(&mut self, len: usize) {
+        let end = (self.pos + len).min(self.src.len());
+        self.out.push_str(&self.src[self.pos..end]);
+        self.origins.extend(self.pos..end);
+        self.pos = end;
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.copy(c.len_utf8());
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.src.len() - self.rest().trim_start().len();
+    }
+
+    /// Write `text` to the output, as having been produced from the position `origin`
+    fn emit(&mut self, text: &str, origin: usize) {
+        self.out.push_str(text);
+        self.origins.extend(std::iter::repeat_n(origin, text.len()));
+    }
+
+    fn emit_from(&mut self, text: &str, origins: &[usize]) {
+        self.out.push_str(text);
+        self.origins.extend_from_slice(origins);
+    }
+
+    /// The length of the string literal at the current position, including its quotes
+    fn string_len(&self) -> usize {
+        let mut chars = self.rest().char_indices();
+        let Some((_, quote)) = chars.next() else {
+            return 0;
+        };
+        while let Some((i, c)) = chars.next() {
+            if c == '\\' {
+                chars.next();
+            } else if c == quote {
+                return i + 1;
+            }
+        }
+        self.rest().len()
+    }
+
+    /// The position in the legacy query that the byte at `position` in the output came from
+    fn origin(&self, position: usize) -> usize {
+        self.origins
+            .get(position)
+            .copied()
+            .unwrap_or(self.src.len())
+    }
+}
+
+/// The length of the number at the start of `input`
+fn number_len(input: &str) -> usize {
+    let mut prev = None;
+    input
+        .find(|c: char| {
+            let end = !(c.is_ascii_digit()
+                || c == '.'
+                || c == 'e'
+                || c == 'E'
+                || (c == '-' && prev.is_none())
+                || ((c == '-' || c == '+') && matches!(prev, Some('e' | 'E'))));
+            prev = Some(c);
+            end
+        })
+        .unwrap_or(input.len())
+}
+
+/// Parse a script expression of the form `(@.length-N)`, producing its length and `N`
+fn parse_length_offset(input: &str) -> Option<(usize, u64)> {
+    let rest = input.strip_prefix('(')?.trim_start();
+    let rest = rest.strip_prefix("@.length")?.trim_start();
+    let rest = rest.strip_prefix('-')?.trim_start();
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let n = rest[..digits].parse().ok()?;
+    let rest = rest[digits..].trim_start().strip_prefix(')')?;
+    Some((input.len() - rest.len(), n))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JsonPath;
+
+    #[test]
+    fn conversions() {
+        for (legacy, rfc) in [
+            (
+                "$.store.book[?(@.price < 10)]",
+                "$.store.book[?(@['price']<10)]",
+            ),
+            ("$..book[(@.length-1)]", "$..book[-1]"),
+            ("$..book[( @.length - 2 )].title", "$..book[-2].title"),
+            ("$[\"a\",\"b\"]", "$['a','b']"),
+            ("$.store.book-title", "$.store['book-title']"),
+            ("$..2024", "$..['2024']"),
+            (
+                "$.book[?(@.author =~ /.*Tolkien/)]",
+                "$.book[?(match(@['author'],'.*Tolkien'))]",
+            ),
+            (
+                "$.book[?(@.author =~ /it\\/s 'x'\\d/i)]",
+                "$.book[?(match(@['author'],'(?i)it/s \\'x\\'\\\\d'))]",
+            ),
+            (
+                "$.book[?(@.category in ['fiction', \"poetry\"])]",
+                "$.book[?((@['category']=='fiction' || @['category']=='poetry'))]",
+            ),
+            (
+                "$.book[?(@.price nin [1, 2.5] && @.isbn)]",
+                "$.book[?((@['price']!=1 && @['price']!=2.5) && @.isbn)]",
+            ),
+            (
+                "$.book[?(length(@.tags) in [0])]",
+                "$.book[?((length(@['tags'])==0))]",
+            ),
+            (
+                "$.a[?(@.b[?(@.c =~ /x/)])]",
+                "$.a[?(@.b[?(match(@['c'],'x'))])]",
+            ),
+            (
+                "$.a[0, ?@.b in [true, null]]",
+                "$.a[0,?(@['b']==true || @['b']==null)]",
+            ),
+        ] {
+            let path = JsonPath::parse_legacy(legacy).expect(legacy);
+            assert_eq!(path.to_string(), rfc, "{legacy}");
+            assert_eq!(JsonPath::parse(rfc).expect(rfc), path, "{legacy}");
+        }
+    }
+
+    #[test]
+    fn diagnostics() {
+        for (legacy, position, message) in [
+            ("$.book.length()", 6, "functions at the end of a path"),
+            ("$.book[(@.length/2)]", 7, "script expressions"),
+            ("$.book[?(@.tags anyof ['a'])]", 16, "the 'anyof' operator"),
+            ("$.book[?(@.a =~ 'x')]", 16, "expected a regular expression"),
+            ("$.book[?(@.a =~ /x/g)]", 19, "flag 'g'"),
+            ("$.book[?(@.a =~ /x)]", 16, "unterminated"),
+            ("$.book[?(@.a in [])]", 16, "an empty list"),
+            ("$.book[?(@.a in $.b)]", 16, "expected a list"),
+            ("$.book[?(@.a in [@.b])]", 17, "expected a literal"),
+            ("$.book[?(in ['a'])]", 9, "expected a value before 'in'"),
+            // errors found when parsing the converted query refer to the legacy query:
+            (
+                "$.book[?(@.a in ['x']) && @.b =~ /y/ &&]",
+                37,
+                "expected closing ']'",
+            ),
+        ] {
+            let err = JsonPath::parse_legacy(legacy).expect_err(legacy);
+            assert_eq!(err.position(), position, "{legacy}: {err}");
+            assert!(err.message().contains(message), "{legacy}: {err}");
+        }
+    }
+}
@@ -317,14 +317,41 @@
 //! The following feature flags are supported:
 //!
 //! - `tracing` - Enable internal tracing via [tracing](https://docs.rs/tracing/latest/tracing/)
+//! - `arithmetic` - Enable the non-standard arithmetic operators, `+`, `-`, `*`, `/` and `%`, in
+//!   the comparisons of filter expressions, e.g., `$[?@.price * @.qty > 100]`. Operands that are
+//!   not numbers, and division by zero, produce nothing
+//! - `datetime` - Enable the non-standard date-time [functions], e.g., `timestamp` and
+//!   `date_before`
 //! - `functions` - Enable user-defined functions
+//! - `legacy` - Enable `JsonPath::parse_legacy`, for converting queries written in the legacy
+//!   JSONPath syntax used by Goessner and Jayway to RFC 9535 queries
+//! - `location-functions` - Enable the non-standard `key` and `index` [functions], for
+//!   referring to the member name or array index of the node being tested by a filter
+//! - `membership` - Enable the non-standard membership operators, `in`, `nin` and `contains`,
+//!   along with lists of literals, in filter expressions, e.g.,
+//!   `$[?@.status in ['open', 'pending']]` or `$[?@.tags contains 'x']`. Lists of literals are
+//!   hashed when the query is parsed
+//! - `numeric-functions` - Enable the non-standard numeric and aggregate [functions], e.g., `sum`
+//!   and `round`
+//! - `parent-selector` - Enable the non-standard parent segment, `^`, which selects the parent
+//!   of each node, e.g., `$..lines[?@.sku == 'X']^^` selects the values containing the `lines`
+//!   that have an item with the `sku` of `'X'`. Parents are only selected once, in document
+//!   order. Parent segments can not be used in singular queries, so `@^.id == 1` is not a valid
+//!   comparison, but they can be used in existence tests and function arguments, e.g.,
+//!   `$..[?@^.id]` or `$..[?count(@^.*) > 1]`
 //! - `regex` - Enable the `match` and `search` functions
+//! - `string-functions` - Enable the non-standard string [functions], e.g., `lower` and
+//!   `starts_with`
+//! - `type-functions` - Enable the non-standard type introspection [functions], e.g., `type`
+//!   and `is_array`
+//! - `variables` - Enable the non-standard `%name` syntax for referring to variables bound in a
+//!   [`Context`] within filter expressions, using [`JsonPath::query_with`]
 
 #![warn(
     clippy::all,
     clippy::dbg_macro,
     clippy::todo,
-    clippy::empty_enum,
+    clippy::empty_enums,
     clippy::enum_glob_use,
     clippy::mem_forget,
     clippy::unused_self,
@@ -335,7 +362,6 @@
     clippy::if_let_mutex,
     unexpected_cfgs,
     clippy::await_holding_lock,
-    clippy::match_on_vec_items,
     clippy::imprecise_flops,
     clippy::suboptimal_flops,
     clippy::lossy_float_literal,
@@ -356,20 +382,51 @@
     missing_docs
 )]
 #![deny(unreachable_pub)]
-#![allow(elided_lifetimes_in_paths, clippy::type_complexity)]
+#![allow(
+    elided_lifetimes_in_paths,
+    mismatched_lifetime_syntaxes,
+    clippy::type_complexity
+)]
 #![forbid(unsafe_code)]
 
+mod analysis;
+mod diff;
 mod error;
 mod ext;
+mod flatten;
+#[cfg(feature = "legacy")]
+mod legacy;
 mod parser;
+mod patch;
 mod path;
+mod pointer;
+mod project;
+mod redact;
+mod table;
 
 #[doc(inline)]
-pub use error::ParseError;
+pub use analysis::{CostBound, QueryAnalysis};
+#[doc(inline)]
+pub use diff::{diff, diff_with, ArrayDiff, Change, DiffOptions};
+#[doc(inline)]
+pub use error::{
+    JsonPointerError, ParseError, PatchError, PatchErrorKind, TableError, UnflattenError,
+    UnflattenErrorKind,
+};
 #[doc(inline)]
 pub use ext::JsonPathExt;
 #[doc(inline)]
+pub use flatten::{flatten, unflatten};
+#[doc(inline)]
+pub use patch::{apply_patch, PatchOperation};
+#[doc(inline)]
 pub use path::JsonPath;
+#[doc(inline)]
+pub use project::{project, ArrayProjection};
+#[doc(inline)]
+pub use redact::{Redaction, Redactor};
+#[doc(inline)]
+pub use serde_json_path_core::context::{Context, EvaluationError};
 /// A list of nodes resulting from a JSONPath query, along with their locations
 ///
 /// This is produced by the [`JsonPath::query_located`] method.
@@ -390,10 +447,17 @@ pub use serde_json_path_core::node::{
 /// A [`NormalizedPath`] is used to represent the location of a node within a query result
 /// produced by the [`JsonPath::query_located`] method.
 ///
+/// The names in a [`NormalizedPath`] produced by a query borrow from the queried
+/// [`serde_json::Value`]; use [`into_owned`][NormalizedPath::into_owned] to produce a
+/// `NormalizedPath<'static>` that can outlive it. Owned paths can also be parsed from, and
+/// deserialized as, strings in the canonical normalized path syntax, e.g., `$['foo'][0]`.
+///
 /// [norm-path]: https://www.rfc-editor.org/rfc/rfc9535.html#name-normalized-paths
 pub use serde_json_path_core::path::NormalizedPath;
 #[doc(inline)]
-pub use serde_json_path_core::path::PathElement;
+pub use serde_json_path_core::path::{ParseNormalizedPathError, PathElement, ResolveError};
+#[doc(inline)]
+pub use table::{Cardinality, TableExtractor};
 
 pub use serde_json_path_core::spec::functions;
 
@@ -444,8 +508,80 @@ pub use serde_json_path_core::spec::functions;
 /// [`LogicalType`][functions::LogicalType].
 ///
 /// When defining your own JSONPath functions, you must use only these types as arguments to your
-/// functions. In addition, your function must return one of these types. If you do not, the macro
-/// will produce a helpful compiler error.
+/// functions, or one of the Rust types described below. In addition, your function must return
+/// one of these types. If you do not, the macro will produce a helpful compiler error.
+///
+/// # Using Rust types
+///
+/// Arguments and return types can also be declared using Rust types, which are converted to and
+/// from the JSONPath types for you:
+///
+/// | Rust type | JSONPath type | Conversion |
+/// |-----------|---------------|------------|
+/// | `&str`, `f64`, `i64`, `&Value`, `&Number` argument | `ValueType` | If the value does not convert, the function is not called, and produces nothing, i.e., `false` if it returns a `LogicalType` |
+/// | `Option<T>` argument, for any `T` above | `ValueType` | If the value does not convert, the argument is `None` |
+/// | `bool` argument or return type | `LogicalType` | |
+/// | `f64`, `i64`, `String`, `Value`, `Number` return type | `ValueType` | |
+/// | `Option<T>` return type, for `T` above or `bool` | `ValueType` | `None` produces nothing |
+///
+/// An `i64` argument accepts numbers with no fractional part, e.g., `2.0`, since they compare
+/// equal to integers in JSONPath.
+///
+/// ```
+/// # use serde_json::json;
+/// # use serde_json_path::JsonPath;
+/// #[serde_json_path::function]
+/// fn discount(price: f64, percent: Option<i64>) -> Option<f64> {
+///     let discounted = price * (100 - percent.unwrap_or(0)) as f64 / 100.0;
+///     (discounted >= 0.0).then_some(discounted)
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let value = json!([{"price": 10, "off": 50}, {"price": 4}, {"price": "unknown"}]);
+/// let path = JsonPath::parse("$[? discount(@.price, @.off) <= 5 ]")?;
+/// assert_eq!(path.query(&value).len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Optional and variadic arguments
+///
+/// Parameters at the end of the parameter list can be marked `#[optional]`, in which case they
+/// must be declared as an `Option<T>`, and receive `None` when not given in the query. The last
+/// parameter can instead be marked `#[variadic]`, in which case it must be declared as a
+/// `Vec<T>`, and receives all remaining arguments, of which there can be any number, including
+/// none. In both cases, `T` can be any of the argument types described above.
+///
+/// ```
+/// # use serde_json::json;
+/// # use serde_json_path::JsonPath;
+/// #[serde_json_path::function]
+/// fn join(#[optional] separator: Option<&str>, #[variadic] parts: Vec<&str>) -> String {
+///     parts.join(separator.unwrap_or(""))
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let value = json!([{"first": "Ada", "last": "Lovelace"}]);
+/// let path = JsonPath::parse("$[? join(' ', @.first, @.last) == 'Ada Lovelace' ]")?;
+/// assert_eq!(path.query(&value).len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Accessing the evaluation context
+///
+/// A function can also declare one parameter of type
+/// [`&FunctionContext`][functions::FunctionContext], in any position. This is not a JSONPath
+/// argument, so it is not passed in queries, but it gives the function access to the root node,
+/// the current node and its location, and the [`Context`] supplied using
+/// [`JsonPath::query_with_context`].
+///
+/// # Fallible functions
+///
+/// A function can return a `Result` whose success type is one of the above types, and whose
+/// error type can be converted into a `Box<dyn std::error::Error + Send + Sync>`, e.g., any error
+/// type, or a `String`. A function that fails produces nothing when using [`JsonPath::query`],
+/// while [`JsonPath::try_query`] produces the error.
 ///
 /// # Override function name using the `name` argument
 ///
@@ -6,10 +6,12 @@ use nom::combinator::all_consuming;
 use nom::error::{ContextError, ErrorKind, FromExternalError, ParseError};
 use nom::Offset;
 use nom::{branch::alt, combinator::map, multi::many0, sequence::preceded, IResult};
+use serde_json_path_core::spec::functions::{FunctionExpr, Validated};
 use serde_json_path_core::spec::query::{Query, QueryKind};
 use serde_json_path_core::spec::segment::QuerySegment;
 
 use self::segment::parse_segment;
+use self::selector::function::parse_function_expr;
 
 pub(crate) mod primitive;
 pub(crate) mod segment;
@@ -160,6 +162,28 @@ pub(crate) fn parse_query_main(input: &str) -> PResult<Query> {
     all_consuming(parse_root_query)(input)
 }
 
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+pub(crate) fn parse_relative_query_main(input: &str) -> PResult<Query> {
+    all_consuming(parse_current_query)(input)
+}
+
+/// A parsed column expression of a [`TableExtractor`][crate::TableExtractor]
+#[derive(Debug, Clone)]
+pub(crate) enum ParsedColumn {
+    /// A query, which is relative to the row if it starts with `@`, or to the root otherwise
+    Query(Query),
+    /// A function expression, e.g., `count(@.items[*])`
+    Function(FunctionExpr<Validated>),
+}
+
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+pub(crate) fn parse_column_main(input: &str) -> PResult<ParsedColumn> {
+    all_consuming(alt((
+        map(parse_query, ParsedColumn::Query),
+        map(parse_function_expr, ParsedColumn::Function),
+    )))(input)
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json_path_core::spec::{
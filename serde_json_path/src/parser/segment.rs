@@ -121,8 +121,29 @@ fn parse_descendant_segment(input: &str) -> PResult<Segment> {
     )(input)
 }
 
+#[cfg(feature = "parent-selector")]
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_parent_segment(input: &str) -> PResult<QuerySegment> {
+    map(preceded(multispace0, char('^')), |_| QuerySegment {
+        kind: QuerySegmentKind::Child,
+        segment: Segment::Parent,
+    })(input)
+}
+
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
 pub(crate) fn parse_segment(input: &str) -> PResult<QuerySegment> {
+    #[cfg(feature = "parent-selector")]
+    {
+        alt((parse_standard_segment, parse_parent_segment))(input)
+    }
+    #[cfg(not(feature = "parent-selector"))]
+    {
+        parse_standard_segment(input)
+    }
+}
+
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_standard_segment(input: &str) -> PResult<QuerySegment> {
     alt((
         map(parse_descendant_segment, |inner| QuerySegment {
             kind: QuerySegmentKind::Descendant,
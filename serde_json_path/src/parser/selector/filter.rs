@@ -1,5 +1,5 @@
 use nom::character::complete::{char, multispace0};
-use nom::combinator::{cut, map, map_res};
+use nom::combinator::{cut, map, map_res, peek};
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
 use nom::{branch::alt, bytes::complete::tag, combinator::value};
@@ -11,11 +11,24 @@ use serde_json_path_core::spec::selector::filter::{
     LogicalAndExpr, LogicalOrExpr, SingularQuery,
 };
 
-use super::function::parse_function_expr;
+#[cfg(feature = "membership")]
+use nom::{character::complete::multispace1, multi::separated_list0};
+#[cfg(feature = "arithmetic")]
+use serde_json_path_core::spec::selector::filter::{ArithmeticExpr, ArithmeticOperator};
+#[cfg(feature = "membership")]
+use serde_json_path_core::spec::selector::filter::{
+    ArrayOperand, LiteralList, MembershipExpr, MembershipOperator,
+};
+#[cfg(feature = "variables")]
+use serde_json_path_core::spec::selector::filter::{NonSingularQueryError, SingularQueryKind};
+
+use super::function::{parse_function_expr, parse_function_name};
 use crate::parser::primitive::number::parse_number;
 use crate::parser::primitive::string::parse_string_literal;
 use crate::parser::primitive::{parse_bool, parse_null};
 use crate::parser::utils::uncut;
+#[cfg(feature = "variables")]
+use crate::parser::{parse_path_segments, segment::parse_dot_member_name};
 use crate::parser::{parse_query, PResult};
 
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
@@ -68,10 +81,13 @@ fn parse_not_exist_expr(input: &str) -> PResult<BasicExpr> {
 
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
 fn parse_func_expr_inner(input: &str) -> PResult<FunctionExpr<Validated>> {
-    cut(map_res(parse_function_expr, |fe| match fe.return_type {
-        FunctionArgType::Logical | FunctionArgType::Nodelist => Ok(fe),
-        _ => Err(FunctionValidationError::IncorrectFunctionReturnType),
-    }))(input)
+    preceded(
+        peek(parse_function_name),
+        cut(map_res(parse_function_expr, |fe| match fe.return_type {
+            FunctionArgType::Logical | FunctionArgType::Nodelist => Ok(fe),
+            _ => Err(FunctionValidationError::IncorrectFunctionReturnType),
+        })),
+    )(input)
 }
 
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
@@ -111,6 +127,18 @@ fn parse_not_parent_expr(input: &str) -> PResult<BasicExpr> {
 
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
 fn parse_basic_expr(input: &str) -> PResult<BasicExpr> {
+    // a comparison may begin with a parenthesized arithmetic expression, so it is tried before
+    // the parenthesized logical expression:
+    #[cfg(feature = "arithmetic")]
+    if let Ok(result) = map(parse_comp_expr, BasicExpr::Relation)(input) {
+        return Ok(result);
+    }
+    // a membership expression begins with a comparable, or a literal list, which would otherwise
+    // be parsed as a comparison or an existence expression:
+    #[cfg(feature = "membership")]
+    if let Ok(result) = map(parse_membership_expr, BasicExpr::Membership)(input) {
+        return Ok(result);
+    }
     alt((
         parse_not_parent_expr,
         parse_paren_expr,
@@ -134,6 +162,60 @@ fn parse_comp_expr(input: &str) -> PResult<ComparisonExpr> {
     )(input)
 }
 
+#[cfg(feature = "membership")]
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_membership_expr(input: &str) -> PResult<MembershipExpr> {
+    alt((
+        map(
+            tuple((
+                parse_comparable,
+                multispace1,
+                alt((
+                    value(MembershipOperator::NotIn, tag("nin")),
+                    value(MembershipOperator::In, tag("in")),
+                )),
+                multispace1,
+                parse_array_operand,
+            )),
+            |(value, _, op, _, array)| MembershipExpr { value, op, array },
+        ),
+        map(
+            separated_pair(
+                parse_array_operand,
+                tuple((multispace1, tag("contains"), multispace1)),
+                parse_comparable,
+            ),
+            |(array, value)| MembershipExpr {
+                value,
+                op: MembershipOperator::Contains,
+                array,
+            },
+        ),
+    ))(input)
+}
+
+#[cfg(feature = "membership")]
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_array_operand(input: &str) -> PResult<ArrayOperand> {
+    alt((
+        map(parse_literal_list, ArrayOperand::List),
+        map(parse_comparable, ArrayOperand::Comparable),
+    ))(input)
+}
+
+#[cfg(feature = "membership")]
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_literal_list(input: &str) -> PResult<LiteralList> {
+    map(
+        delimited(
+            pair(char('['), multispace0),
+            separated_list0(tuple((multispace0, char(','), multispace0)), parse_literal),
+            pair(multispace0, char(']')),
+        ),
+        LiteralList::new,
+    )(input)
+}
+
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
 fn parse_comparison_operator(input: &str) -> PResult<ComparisonOperator> {
     alt((
@@ -162,10 +244,39 @@ fn parse_literal_comparable(input: &str) -> PResult<Comparable> {
 }
 
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
-pub(crate) fn parse_singular_path(input: &str) -> PResult<SingularQuery> {
+fn parse_singular_query(input: &str) -> PResult<SingularQuery> {
     map_res(parse_query, |q| q.try_into())(input)
 }
 
+#[cfg(feature = "variables")]
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_variable_query(input: &str) -> PResult<SingularQuery> {
+    map_res(
+        preceded(char('%'), pair(parse_dot_member_name, parse_path_segments)),
+        |(name, segments)| {
+            Ok::<_, NonSingularQueryError>(SingularQuery {
+                kind: SingularQueryKind::Variable(name),
+                segments: segments
+                    .into_iter()
+                    .map(TryFrom::try_from)
+                    .collect::<Result<_, _>>()?,
+            })
+        },
+    )(input)
+}
+
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+pub(crate) fn parse_singular_path(input: &str) -> PResult<SingularQuery> {
+    #[cfg(feature = "variables")]
+    {
+        alt((parse_singular_query, parse_variable_query))(input)
+    }
+    #[cfg(not(feature = "variables"))]
+    {
+        parse_singular_query(input)
+    }
+}
+
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
 fn parse_singular_path_comparable(input: &str) -> PResult<Comparable> {
     map(parse_singular_path, Comparable::SingularQuery)(input)
@@ -183,12 +294,86 @@ fn parse_function_expr_comparable(input: &str) -> PResult<Comparable> {
 }
 
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
-pub(crate) fn parse_comparable(input: &str) -> PResult<Comparable> {
-    uncut(alt((
+fn parse_comparable_operand(input: &str) -> PResult<Comparable> {
+    alt((
         parse_literal_comparable,
         parse_singular_path_comparable,
         parse_function_expr_comparable,
-    )))(input)
+    ))(input)
+}
+
+#[cfg(feature = "arithmetic")]
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_arithmetic_operand(input: &str) -> PResult<Comparable> {
+    alt((
+        delimited(
+            pair(char('('), multispace0),
+            parse_additive_expr,
+            pair(multispace0, char(')')),
+        ),
+        parse_comparable_operand,
+    ))(input)
+}
+
+/// Parse a chain of left-associative arithmetic operations, whose operators share a precedence
+#[cfg(feature = "arithmetic")]
+fn parse_arithmetic_chain<'a>(
+    input: &'a str,
+    mut parse_operand: impl FnMut(&'a str) -> PResult<'a, Comparable>,
+    mut parse_operator: impl FnMut(&'a str) -> PResult<'a, ArithmeticOperator>,
+) -> PResult<'a, Comparable> {
+    let (mut input, mut left) = parse_operand(input)?;
+    loop {
+        match tuple((
+            multispace0,
+            &mut parse_operator,
+            multispace0,
+            &mut parse_operand,
+        ))(input)
+        {
+            Ok((rest, (_, op, _, right))) => {
+                left = Comparable::Arithmetic(Box::new(ArithmeticExpr { left, op, right }));
+                input = rest;
+            }
+            Err(nom::Err::Error(_)) => return Ok((input, left)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(feature = "arithmetic")]
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_multiplicative_expr(input: &str) -> PResult<Comparable> {
+    parse_arithmetic_chain(input, parse_arithmetic_operand, |i| {
+        alt((
+            value(ArithmeticOperator::Multiply, char('*')),
+            value(ArithmeticOperator::Divide, char('/')),
+            value(ArithmeticOperator::Remainder, char('%')),
+        ))(i)
+    })
+}
+
+#[cfg(feature = "arithmetic")]
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+fn parse_additive_expr(input: &str) -> PResult<Comparable> {
+    parse_arithmetic_chain(input, parse_multiplicative_expr, |i| {
+        alt((
+            value(ArithmeticOperator::Add, char('+')),
+            value(ArithmeticOperator::Subtract, char('-')),
+        ))(i)
+    })
+}
+
+#[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
+pub(crate) fn parse_comparable(input: &str) -> PResult<Comparable> {
+    #[cfg(feature = "arithmetic")]
+    {
+        uncut(parse_additive_expr)(input)
+    }
+    #[cfg(not(feature = "arithmetic"))]
+    {
+        uncut(parse_comparable_operand)(input)
+    }
 }
 
 #[cfg(test)]
@@ -269,4 +454,28 @@ mod tests {
             assert!(matches!(&sp[2], SingularQuerySegment::Name(Name(s)) if s == "id"));
         }
     }
+
+    #[cfg(feature = "variables")]
+    #[test]
+    fn variable_comparables() {
+        use serde_json_path_core::spec::selector::filter::SingularQueryKind;
+        {
+            let (_, cmp) = parse_comparable("%limit").unwrap();
+            let sp = cmp.as_singular_path().unwrap();
+            assert!(matches!(&sp.kind, SingularQueryKind::Variable(n) if n == "limit"));
+            assert!(sp.segments.is_empty());
+        }
+        {
+            let (_, cmp) = parse_comparable("%tenant.limits[1]").unwrap();
+            let sp = cmp.as_singular_path().unwrap();
+            assert!(matches!(&sp.kind, SingularQueryKind::Variable(n) if n == "tenant"));
+            assert!(
+                matches!(&sp.segments[0], SingularQuerySegment::Name(Name(s)) if s == "limits")
+            );
+            assert!(matches!(&sp.segments[1], SingularQuerySegment::Index(Index(i)) if i == &1));
+        }
+        for invalid in ["%", "%1a", "%a.*", "%a[0:1]", "%a..b"] {
+            assert!(parse_comparable(invalid).is_err(), "{invalid}");
+        }
+    }
 }
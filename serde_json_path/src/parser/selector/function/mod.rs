@@ -36,7 +36,7 @@ fn parse_function_name_char(input: &str) -> PResult<char> {
 }
 
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
-fn parse_function_name(input: &str) -> PResult<String> {
+pub(crate) fn parse_function_name(input: &str) -> PResult<String> {
     map(
         pair(
             parse_function_name_first,
@@ -66,19 +66,20 @@ fn parse_function_argument(input: &str) -> PResult<FunctionExprArg> {
 
 #[cfg_attr(feature = "trace", tracing::instrument(level = "trace", parent = None, ret, err))]
 pub(crate) fn parse_function_expr(input: &str) -> PResult<FunctionExpr<Validated>> {
+    // only commit to the function expression once its name is parsed, so that a function with no
+    // arguments, e.g., `f()`, does not fail when its arguments are tried as a function expression:
+    let (input, name) = parse_function_name(input)?;
     cut(map_res(
-        pair(
-            parse_function_name,
-            delimited(
-                terminated(char('('), multispace0),
-                separated_list0(
-                    delimited(multispace0, char(','), multispace0),
-                    parse_function_argument,
-                ),
-                preceded(multispace0, char(')')),
+        delimited(
+            terminated(char('('), multispace0),
+            separated_list0(
+                delimited(multispace0, char(','), multispace0),
+                parse_function_argument,
             ),
+            preceded(multispace0, char(')')),
         ),
-        |(name, args)| {
+        move |args| {
+            let name = name.clone();
             #[cfg(feature = "functions")]
             for f in inventory::iter::<Function> {
                 if f.name == name {
@@ -89,6 +90,7 @@ pub(crate) fn parse_function_expr(input: &str) -> PResult<FunctionExpr<Validated
                         return_type: f.result_type,
                         validated: Validated {
                             evaluator: f.evaluator,
+                            uses_context: f.uses_context,
                         },
                     });
                 }
@@ -101,6 +103,7 @@ pub(crate) fn parse_function_expr(input: &str) -> PResult<FunctionExpr<Validated
                     return_type: f.result_type,
                     validated: Validated {
                         evaluator: f.evaluator,
+                        uses_context: f.uses_context,
                     },
                 });
             }
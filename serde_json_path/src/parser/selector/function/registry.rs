@@ -1,30 +1,77 @@
 use std::{collections::HashMap, sync::LazyLock};
 
+#[cfg(feature = "datetime")]
+use chrono::{DateTime, FixedOffset};
+#[cfg(any(feature = "numeric-functions", feature = "datetime"))]
+use serde_json::Number;
 use serde_json::Value;
+#[cfg(feature = "location-functions")]
+use serde_json_path_core::path::PathElement;
+#[cfg(feature = "location-functions")]
+use serde_json_path_core::spec::functions::FunctionContext;
 use serde_json_path_core::spec::functions::{Function, LogicalType, NodesType, ValueType};
+#[cfg(any(feature = "numeric-functions", feature = "datetime"))]
+use serde_json_path_core::spec::number::Numeric;
 
 /// The main registry of functions for use in JSONPath queries
 ///
 /// These come directly from the JSONPath specification, which includes a registry of standardized
-/// functions.
-///
-/// # Note
-///
-/// There is a function in `serde_json_path_core/src/spec/functions.rs` that gives
-/// the return type for each function registered here. When adding new functions to
-/// the register, i.e., when new functions are standardized, the function there needs
-/// to be updated too.
+/// functions, along with the non-standard functions enabled by feature flags.
 pub(crate) static REGISTRY: LazyLock<HashMap<&'static str, &'static Function>> =
     LazyLock::new(|| {
         let mut m = HashMap::new();
         m.insert("length", &LENGTH_FUNC);
         m.insert("count", &COUNT_FUNC);
+        #[cfg(feature = "type-functions")]
+        {
+            m.insert("type", &TYPE_FUNC);
+            m.insert("is_string", &IS_STRING_FUNC);
+            m.insert("is_number", &IS_NUMBER_FUNC);
+            m.insert("is_boolean", &IS_BOOLEAN_FUNC);
+            m.insert("is_null", &IS_NULL_FUNC);
+            m.insert("is_object", &IS_OBJECT_FUNC);
+            m.insert("is_array", &IS_ARRAY_FUNC);
+        }
         #[cfg(feature = "regex")]
         {
             m.insert("match", &MATCH_FUNC);
             m.insert("search", &SEARCH_FUNC);
         }
         m.insert("value", &VALUE_FUNC);
+        #[cfg(feature = "string-functions")]
+        {
+            m.insert("lower", &LOWER_FUNC);
+            m.insert("upper", &UPPER_FUNC);
+            m.insert("starts_with", &STARTS_WITH_FUNC);
+            m.insert("ends_with", &ENDS_WITH_FUNC);
+            m.insert("contains", &CONTAINS_FUNC);
+            m.insert("substring", &SUBSTRING_FUNC);
+            m.insert("trim", &TRIM_FUNC);
+            m.insert("split_count", &SPLIT_COUNT_FUNC);
+            m.insert("concat", &CONCAT_FUNC);
+        }
+        #[cfg(feature = "numeric-functions")]
+        {
+            m.insert("sum", &SUM_FUNC);
+            m.insert("min", &MIN_FUNC);
+            m.insert("max", &MAX_FUNC);
+            m.insert("avg", &AVG_FUNC);
+            m.insert("abs", &ABS_FUNC);
+            m.insert("floor", &FLOOR_FUNC);
+            m.insert("ceil", &CEIL_FUNC);
+            m.insert("round", &ROUND_FUNC);
+        }
+        #[cfg(feature = "location-functions")]
+        {
+            m.insert("key", &KEY_FUNC);
+            m.insert("index", &INDEX_FUNC);
+        }
+        #[cfg(feature = "datetime")]
+        {
+            m.insert("timestamp", &TIMESTAMP_FUNC);
+            m.insert("date_before", &DATE_BEFORE_FUNC);
+            m.insert("date_diff", &DATE_DIFF_FUNC);
+        }
         m
     });
 
@@ -91,3 +138,296 @@ fn value(nodes: NodesType) -> ValueType {
         }
     }
 }
+
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = LOWER_FUNC)]
+fn lower(s: &str) -> String {
+    s.to_lowercase()
+}
+
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = UPPER_FUNC)]
+fn upper(s: &str) -> String {
+    s.to_uppercase()
+}
+
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = STARTS_WITH_FUNC)]
+fn starts_with(s: &str, prefix: &str) -> bool {
+    s.starts_with(prefix)
+}
+
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = ENDS_WITH_FUNC)]
+fn ends_with(s: &str, suffix: &str) -> bool {
+    s.ends_with(suffix)
+}
+
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = CONTAINS_FUNC)]
+fn contains(s: &str, pattern: &str) -> bool {
+    s.contains(pattern)
+}
+
+/// The characters of `s` from `start`, which counts back from the end of `s` if it is negative,
+/// up to `length` characters, or to the end of `s` if `length` is not given
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = SUBSTRING_FUNC)]
+fn substring(s: &str, start: i64, #[optional] length: Option<i64>) -> Option<String> {
+    let len = i64::try_from(s.chars().count()).ok()?;
+    let start = if start < 0 { len + start } else { start }.clamp(0, len);
+    let length = match length {
+        Some(l) if l < 0 => return None,
+        Some(l) => l,
+        None => len,
+    };
+    Some(
+        s.chars()
+            .skip(usize::try_from(start).ok()?)
+            .take(usize::try_from(length).unwrap_or(usize::MAX))
+            .collect(),
+    )
+}
+
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = TRIM_FUNC)]
+fn trim(s: &str) -> String {
+    s.trim().to_owned()
+}
+
+/// The number of parts that `s` is split into by `separator`, which must not be empty
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = SPLIT_COUNT_FUNC)]
+fn split_count(s: &str, separator: &str) -> Option<i64> {
+    if separator.is_empty() {
+        return None;
+    }
+    i64::try_from(s.split(separator).count()).ok()
+}
+
+#[cfg(feature = "string-functions")]
+#[serde_json_path_macros::register(target = CONCAT_FUNC)]
+fn concat(#[variadic] parts: Vec<&str>) -> String {
+    parts.concat()
+}
+
+/// The numbers in `nodes`, or `None` if any of the nodes are not numbers
+#[cfg(feature = "numeric-functions")]
+fn numbers(nodes: NodesType<'_>) -> Option<Vec<(&Value, Numeric)>> {
+    nodes
+        .into_iter()
+        .map(|v| match v {
+            Value::Number(n) => Some((v, Numeric::from(n))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The node holding the least, or greatest, number in `nodes`
+#[cfg(feature = "numeric-functions")]
+fn extremum<'a>(nodes: NodesType<'a>, ordering: std::cmp::Ordering) -> ValueType<'a> {
+    numbers(nodes)
+        .and_then(|numbers| {
+            numbers
+                .into_iter()
+                .reduce(|a, b| match b.1.partial_cmp(&a.1) {
+                    Some(o) if o == ordering => b,
+                    _ => a,
+                })
+        })
+        .map_or(ValueType::Nothing, |(v, _)| ValueType::Node(v))
+}
+
+#[cfg(feature = "numeric-functions")]
+#[serde_json_path_macros::register(target = SUM_FUNC)]
+fn sum(nodes: NodesType) -> Option<Number> {
+    numbers(nodes)?
+        .into_iter()
+        .map(|(_, n)| n)
+        .fold(Numeric::Integer(0), Numeric::add)
+        .into_number()
+}
+
+#[cfg(feature = "numeric-functions")]
+#[serde_json_path_macros::register(target = MIN_FUNC)]
+fn min(nodes: NodesType) -> ValueType {
+    extremum(nodes, std::cmp::Ordering::Less)
+}
+
+#[cfg(feature = "numeric-functions")]
+#[serde_json_path_macros::register(target = MAX_FUNC)]
+fn max(nodes: NodesType) -> ValueType {
+    extremum(nodes, std::cmp::Ordering::Greater)
+}
+
+#[cfg(feature = "numeric-functions")]
+#[serde_json_path_macros::register(target = AVG_FUNC)]
+fn avg(nodes: NodesType) -> Option<Number> {
+    let numbers = numbers(nodes)?;
+    if numbers.is_empty() {
+        return None;
+    }
+    let sum = numbers
+        .iter()
+        .map(|(_, n)| *n)
+        .fold(Numeric::Integer(0), Numeric::add);
+    Numeric::Float(sum.as_f64() / numbers.len() as f64).into_number()
+}
+
+#[cfg(feature = "numeric-functions")]
+#[serde_json_path_macros::register(target = ABS_FUNC)]
+fn abs(n: &Number) -> Option<Number> {
+    Numeric::from(n).abs().into_number()
+}
+
+#[cfg(feature = "numeric-functions")]
+#[serde_json_path_macros::register(target = FLOOR_FUNC)]
+fn floor(n: &Number) -> Option<Number> {
+    Numeric::from(n).floor().into_number()
+}
+
+#[cfg(feature = "numeric-functions")]
+#[serde_json_path_macros::register(target = CEIL_FUNC)]
+fn ceil(n: &Number) -> Option<Number> {
+    Numeric::from(n).ceil().into_number()
+}
+
+/// Round `n` to the nearest integer, or to `digits` decimal places
+#[cfg(feature = "numeric-functions")]
+#[serde_json_path_macros::register(target = ROUND_FUNC)]
+fn round(n: &Number, #[optional] digits: Option<i64>) -> Option<Number> {
+    let n = Numeric::from(n);
+    match (n, digits) {
+        (_, None) | (Numeric::Integer(_), Some(0..)) => n.round().into_number(),
+        (_, Some(digits)) => {
+            let factor = 10f64.powi(i32::try_from(digits.clamp(-400, 400)).ok()?);
+            let scaled = n.as_f64() * factor;
+            if !scaled.is_finite() {
+                // there are more decimal places than a float can hold, so `n` is already rounded
+                return n.into_number();
+            }
+            let rounded = if factor == 0.0 {
+                0.0
+            } else {
+                scaled.round() / factor
+            };
+            Numeric::Float(rounded).into_number()
+        }
+    }
+}
+
+#[cfg(feature = "type-functions")]
+#[serde_json_path_macros::register(name = "type", target = TYPE_FUNC)]
+fn type_func(value: &Value) -> String {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+    .to_owned()
+}
+
+#[cfg(feature = "type-functions")]
+#[serde_json_path_macros::register(target = IS_STRING_FUNC)]
+fn is_string(value: &Value) -> bool {
+    value.is_string()
+}
+
+#[cfg(feature = "type-functions")]
+#[serde_json_path_macros::register(target = IS_NUMBER_FUNC)]
+fn is_number(value: &Value) -> bool {
+    value.is_number()
+}
+
+#[cfg(feature = "type-functions")]
+#[serde_json_path_macros::register(target = IS_BOOLEAN_FUNC)]
+fn is_boolean(value: &Value) -> bool {
+    value.is_boolean()
+}
+
+#[cfg(feature = "type-functions")]
+#[serde_json_path_macros::register(target = IS_NULL_FUNC)]
+fn is_null(value: &Value) -> bool {
+    value.is_null()
+}
+
+#[cfg(feature = "type-functions")]
+#[serde_json_path_macros::register(target = IS_OBJECT_FUNC)]
+fn is_object(value: &Value) -> bool {
+    value.is_object()
+}
+
+#[cfg(feature = "type-functions")]
+#[serde_json_path_macros::register(target = IS_ARRAY_FUNC)]
+fn is_array(value: &Value) -> bool {
+    value.is_array()
+}
+
+#[cfg(feature = "location-functions")]
+#[serde_json_path_macros::register(target = KEY_FUNC)]
+fn key(ctx: &FunctionContext) -> Option<String> {
+    match ctx.location()?.last()? {
+        PathElement::Name(name) => Some(name.to_string()),
+        PathElement::Index(_) => None,
+    }
+}
+
+#[cfg(feature = "location-functions")]
+#[serde_json_path_macros::register(target = INDEX_FUNC)]
+fn index(ctx: &FunctionContext) -> Option<i64> {
+    match ctx.location()?.last()? {
+        PathElement::Name(_) => None,
+        PathElement::Index(index) => i64::try_from(*index).ok(),
+    }
+}
+
+/// The number of nanoseconds since the Unix epoch of an RFC 3339 date-time
+#[cfg(feature = "datetime")]
+fn epoch_nanos(s: &str) -> Option<i128> {
+    DateTime::<FixedOffset>::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| {
+            i128::from(dt.timestamp()) * 1_000_000_000 + i128::from(dt.timestamp_subsec_nanos())
+        })
+}
+
+/// Divide a number of nanoseconds into whole units, exactly where possible
+#[cfg(feature = "datetime")]
+fn nanos_in(nanos: i128, unit: i128) -> Option<Number> {
+    if nanos % unit == 0 {
+        Numeric::Integer(nanos / unit)
+    } else {
+        Numeric::Float(nanos as f64 / unit as f64)
+    }
+    .into_number()
+}
+
+#[cfg(feature = "datetime")]
+#[serde_json_path_macros::register(target = TIMESTAMP_FUNC)]
+fn timestamp(s: &str) -> Option<Number> {
+    nanos_in(epoch_nanos(s)?, 1_000_000_000)
+}
+
+#[cfg(feature = "datetime")]
+#[serde_json_path_macros::register(target = DATE_BEFORE_FUNC)]
+fn date_before(a: &str, b: &str) -> bool {
+    matches!((epoch_nanos(a), epoch_nanos(b)), (Some(a), Some(b)) if a < b)
+}
+
+#[cfg(feature = "datetime")]
+#[serde_json_path_macros::register(target = DATE_DIFF_FUNC)]
+fn date_diff(a: &str, b: &str, unit: &str) -> Option<Number> {
+    let unit = match unit {
+        "milliseconds" => 1_000_000,
+        "seconds" => 1_000_000_000,
+        "minutes" => 60_000_000_000,
+        "hours" => 3_600_000_000_000,
+        "days" => 86_400_000_000_000,
+        "weeks" => 604_800_000_000_000,
+        _ => return None,
+    };
+    nanos_in(epoch_nanos(a)? - epoch_nanos(b)?, unit)
+}
@@ -0,0 +1,564 @@
+//! Generation and application of [JSON Patch][json-patch] documents
+//!
+//! [json-patch]: https://datatracker.ietf.org/doc/html/rfc6902
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_json_path_core::{node::LocatedNode, path::NormalizedPath, spec::number::Numeric};
+
+use crate::{
+    pointer::{array_index, reference_tokens},
+    JsonPath, PatchError, PatchErrorKind,
+};
+
+/// A single [JSON Patch][json-patch] operation
+///
+/// Each operation targets a location, `path`, given as a JSON Pointer string. Operations are
+/// serialized using the representation defined in RFC 6902, e.g.,
+/// `{"op": "replace", "path": "/foo/0", "value": 42}`, so a `Vec<PatchOperation>` can be
+/// serialized as a complete JSON Patch document.
+///
+/// Patches can be produced from the nodes matched by a [`JsonPath`] using the
+/// [`set_patch`][JsonPath::set_patch], [`remove_patch`][JsonPath::remove_patch] and
+/// [`transform_patch`][JsonPath::transform_patch] methods, and can be applied to a
+/// [`serde_json::Value`] using [`apply_patch`].
+///
+/// [json-patch]: https://datatracker.ietf.org/doc/html/rfc6902
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Add `value` at `path`, inserting it into an array, or replacing an object member
+    Add {
+        /// The target location
+        path: String,
+        /// The value to add
+        value: Value,
+    },
+    /// Remove the node at `path`
+    Remove {
+        /// The target location
+        path: String,
+    },
+    /// Replace the node at `path` with `value`
+    Replace {
+        /// The target location
+        path: String,
+        /// The replacement value
+        value: Value,
+    },
+    /// Remove the node at `from`, and add it at `path`
+    Move {
+        /// The location of the node to move
+        from: String,
+        /// The target location
+        path: String,
+    },
+    /// Add a copy of the node at `from` at `path`
+    Copy {
+        /// The location of the node to copy
+        from: String,
+        /// The target location
+        path: String,
+    },
+    /// Test that the node at `path` is equal to `value`
+    Test {
+        /// The target location
+        path: String,
+        /// The expected value
+        value: Value,
+    },
+}
+
+impl JsonPath {
+    /// Produce a JSON Patch that replaces each node matched by this [`JsonPath`] with `new`
+    ///
+    /// This does not modify `value`; the produced patch can be applied using [`apply_patch`].
+    /// If both a node and one of its descendants are matched, only the node is replaced.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::{apply_patch, JsonPath};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut value = json!({"items": [{"price": 10}, {"price": 20}]});
+    /// let path = JsonPath::parse("$.items[?@.price > 15].price")?;
+    /// let patch = path.set_patch(&value, json!(15));
+    /// assert_eq!(
+    ///     serde_json::to_value(&patch)?,
+    ///     json!([{"op": "replace", "path": "/items/1/price", "value": 15}]),
+    /// );
+    /// apply_patch(&mut value, &patch)?;
+    /// assert_eq!(value, json!({"items": [{"price": 10}, {"price": 15}]}));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_patch(&self, value: &Value, new: Value) -> Vec<PatchOperation> {
+        self.transform_patch(value, |_| new.clone())
+    }
+
+    /// Produce a JSON Patch that removes each node matched by this [`JsonPath`]
+    ///
+    /// This does not modify `value`; the produced patch can be applied using [`apply_patch`].
+    /// The operations are ordered such that removing an array element does not change the
+    /// location of any node that is removed after it. If both a node and one of its descendants
+    /// are matched, only the node is removed. The root node can not be removed by a JSON Patch,
+    /// so it is left out of the patch if it is matched, while any other matched nodes are still
+    /// removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::{apply_patch, JsonPath};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut value = json!([1, 2, 3, 4]);
+    /// let path = JsonPath::parse("$[?@ > 2]")?;
+    /// let patch = path.remove_patch(&value);
+    /// assert_eq!(
+    ///     serde_json::to_value(&patch)?,
+    ///     json!([{"op": "remove", "path": "/3"}, {"op": "remove", "path": "/2"}]),
+    /// );
+    /// apply_patch(&mut value, &patch)?;
+    /// assert_eq!(value, json!([1, 2]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remove_patch(&self, value: &Value) -> Vec<PatchOperation> {
+        let locations = self
+            .query_located(value)
+            .dedup()
+            .into_iter()
+            .filter(|node| !node.location().is_empty());
+        outermost_locations(locations)
+            .iter()
+            .rev()
+            .map(|path| PatchOperation::Remove {
+                path: path.to_json_pointer(),
+            })
+            .collect()
+    }
+
+    /// Produce a JSON Patch that replaces each node matched by this [`JsonPath`] with the result
+    /// of calling `f` on it
+    ///
+    /// This does not modify `value`; the produced patch can be applied using [`apply_patch`].
+    /// Nodes that are not changed by `f` are left out of the patch. If both a node and one of its
+    /// descendants are matched, `f` is only called for the node.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::{apply_patch, JsonPath};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut value = json!({"names": ["Alice", "bob"]});
+    /// let path = JsonPath::parse("$.names[*]")?;
+    /// let patch = path.transform_patch(&value, |node| {
+    ///     node.as_str().map(str::to_lowercase).into()
+    /// });
+    /// assert_eq!(patch.len(), 1);
+    /// apply_patch(&mut value, &patch)?;
+    /// assert_eq!(value, json!({"names": ["alice", "bob"]}));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transform_patch<F>(&self, value: &Value, mut f: F) -> Vec<PatchOperation>
+    where
+        F: FnMut(&Value) -> Value,
+    {
+        outermost_locations(self.query_located(value).dedup())
+            .into_iter()
+            .filter_map(|path| {
+                let node = path
+                    .resolve(value)
+                    .expect("location of matched node is valid");
+                let new = f(node);
+                (&new != node).then(|| PatchOperation::Replace {
+                    path: path.to_json_pointer(),
+                    value: new,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Get the de-duplicated locations of matched nodes, in document order, without those that are
+/// descendants of another matched node
+fn outermost_locations<'b, I>(matched: I) -> Vec<NormalizedPath<'b>>
+where
+    I: IntoIterator<Item = LocatedNode<'b>>,
+{
+    let mut locations: Vec<NormalizedPath<'b>> = Vec::new();
+    for loc in matched {
+        let loc = NormalizedPath::from(loc);
+        if !locations.last().is_some_and(|l| loc.starts_with(l)) {
+            locations.push(loc);
+        }
+    }
+    locations
+}
+
+/// Apply a [JSON Patch][json-patch] to a [`serde_json::Value`]
+///
+/// The operations are applied in order. If any of them fails, an error is returned and `value` is
+/// left unchanged.
+///
+/// # Example
+/// ```rust
+/// # use serde_json::json;
+/// # use serde_json_path::{apply_patch, PatchOperation};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut value = json!({"foo": ["bar"]});
+/// let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+///     {"op": "test", "path": "/foo/0", "value": "bar"},
+///     {"op": "add", "path": "/foo/-", "value": "baz"},
+///     {"op": "move", "from": "/foo", "path": "/bop"},
+/// ]))?;
+/// apply_patch(&mut value, &patch)?;
+/// assert_eq!(value, json!({"bop": ["bar", "baz"]}));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [json-patch]: https://datatracker.ietf.org/doc/html/rfc6902
+pub fn apply_patch(value: &mut Value, patch: &[PatchOperation]) -> Result<(), PatchError> {
+    let mut patched = value.clone();
+    for (i, operation) in patch.iter().enumerate() {
+        apply_operation(&mut patched, operation).map_err(|kind| PatchError::new(i, kind))?;
+    }
+    *value = patched;
+    Ok(())
+}
+
+fn apply_operation(value: &mut Value, operation: &PatchOperation) -> Result<(), PatchErrorKind> {
+    match operation {
+        PatchOperation::Add { path, value: new } => add(value, path, new.clone()),
+        PatchOperation::Remove { path } => remove(value, path).map(|_| ()),
+        PatchOperation::Replace { path, value: new } => {
+            *get_mut(value, &reference_tokens(path)?, path)? = new.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            let from_tokens = reference_tokens(from)?;
+            let path_tokens = reference_tokens(path)?;
+            if path_tokens.len() > from_tokens.len() && path_tokens.starts_with(&from_tokens) {
+                return Err(PatchErrorKind::MoveIntoDescendant {
+                    from: from.to_owned(),
+                    path: path.to_owned(),
+                });
+            }
+            let node = remove(value, from)?;
+            add(value, path, node)
+        }
+        PatchOperation::Copy { from, path } => {
+            let node = get_mut(value, &reference_tokens(from)?, from)?.clone();
+            add(value, path, node)
+        }
+        PatchOperation::Test {
+            path,
+            value: expected,
+        } => {
+            let node = get_mut(value, &reference_tokens(path)?, path)?;
+            if json_eq(node, expected) {
+                Ok(())
+            } else {
+                Err(PatchErrorKind::TestFailed {
+                    path: path.to_owned(),
+                })
+            }
+        }
+    }
+}
+
+/// Get the node located by the reference `tokens` of the `pointer`
+fn get_mut<'v>(
+    value: &'v mut Value,
+    tokens: &[String],
+    pointer: &str,
+) -> Result<&'v mut Value, PatchErrorKind> {
+    tokens
+        .iter()
+        .try_fold(value, |node, token| match node {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(list) => array_index(token).and_then(|i| list.get_mut(i)),
+            _ => None,
+        })
+        .ok_or_else(|| PatchErrorKind::NotFound {
+            path: pointer.to_owned(),
+        })
+}
+
+fn add(value: &mut Value, pointer: &str, node: Value) -> Result<(), PatchErrorKind> {
+    let tokens = reference_tokens(pointer)?;
+    let Some((last, parent)) = tokens.split_last() else {
+        *value = node;
+        return Ok(());
+    };
+    let not_found = || PatchErrorKind::NotFound {
+        path: pointer.to_owned(),
+    };
+    match get_mut(value, parent, pointer)? {
+        Value::Object(map) => {
+            map.insert(last.to_owned(), node);
+        }
+        Value::Array(list) if last == "-" => list.push(node),
+        Value::Array(list) => match array_index(last) {
+            Some(i) if i <= list.len() => list.insert(i, node),
+            _ => return Err(not_found()),
+        },
+        _ => return Err(not_found()),
+    }
+    Ok(())
+}
+
+fn remove(value: &mut Value, pointer: &str) -> Result<Value, PatchErrorKind> {
+    let tokens = reference_tokens(pointer)?;
+    let Some((last, parent)) = tokens.split_last() else {
+        return Err(PatchErrorKind::RemoveRoot);
+    };
+    let removed = match get_mut(value, parent, pointer)? {
+        Value::Object(map) => map.remove(last),
+        Value::Array(list) => array_index(last)
+            .filter(|i| *i < list.len())
+            .map(|i| list.remove(i)),
+        _ => None,
+    };
+    removed.ok_or_else(|| PatchErrorKind::NotFound {
+        path: pointer.to_owned(),
+    })
+}
+
+/// Compare two values for equality, as per the JSON Patch `test` operation
+///
+/// This differs from the `PartialEq` implementation of [`serde_json::Value`] in that numbers are
+/// compared exactly by their numeric value, e.g., `1` is equal to `1.0`.
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => Numeric::from(a) == Numeric::from(b),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, a)| b.get(k).is_some_and(|b| json_eq(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_value, json};
+
+    use super::{apply_patch, PatchOperation};
+    use crate::{JsonPath, PatchErrorKind};
+
+    fn patch(ops: serde_json::Value) -> Vec<PatchOperation> {
+        from_value(ops).expect("valid patch")
+    }
+
+    #[test]
+    fn rfc6902_examples() {
+        for (doc, ops, exp) in [
+            // A.1 - A.4
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz", "value": "qux"}]),
+                json!({"baz": "qux", "foo": "bar"}),
+            ),
+            (
+                json!({"foo": ["bar", "baz"]}),
+                json!([{"op": "add", "path": "/foo/1", "value": "qux"}]),
+                json!({"foo": ["bar", "qux", "baz"]}),
+            ),
+            (
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "remove", "path": "/baz"}]),
+                json!({"foo": "bar"}),
+            ),
+            (
+                json!({"foo": ["bar", "qux", "baz"]}),
+                json!([{"op": "remove", "path": "/foo/1"}]),
+                json!({"foo": ["bar", "baz"]}),
+            ),
+            // A.5 - A.7
+            (
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "replace", "path": "/baz", "value": "boo"}]),
+                json!({"baz": "boo", "foo": "bar"}),
+            ),
+            (
+                json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+                json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]),
+                json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}),
+            ),
+            (
+                json!({"foo": ["all", "grass", "cows", "eat"]}),
+                json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}]),
+                json!({"foo": ["all", "cows", "eat", "grass"]}),
+            ),
+            // A.8, A.10, A.14, A.16
+            (
+                json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+                json!([{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]),
+                json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+            ),
+            (
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/child", "value": {"grandchild": {}}}]),
+                json!({"foo": "bar", "child": {"grandchild": {}}}),
+            ),
+            (
+                json!({"/": 9, "~1": 10}),
+                json!([{"op": "test", "path": "/~01", "value": 10}]),
+                json!({"/": 9, "~1": 10}),
+            ),
+            (
+                json!({"foo": ["bar"]}),
+                json!([{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]),
+                json!({"foo": ["bar", ["abc", "def"]]}),
+            ),
+            // other
+            (
+                json!({"a": 1}),
+                json!([{"op": "copy", "from": "/a", "path": "/b"}, {"op": "test", "path": "/b", "value": 1.0}]),
+                json!({"a": 1, "b": 1}),
+            ),
+            (
+                json!({"a": 1}),
+                json!([{"op": "add", "path": "", "value": [1]}]),
+                json!([1]),
+            ),
+        ] {
+            let mut value = doc.clone();
+            apply_patch(&mut value, &patch(ops.clone())).unwrap_or_else(|e| panic!("{ops}: {e}"));
+            assert_eq!(value, exp, "{ops}");
+        }
+    }
+
+    #[test]
+    fn errors_leave_value_unchanged() {
+        for (ops, kind) in [
+            // A.9, A.12, A.15
+            (
+                json!([{"op": "test", "path": "/baz", "value": "bar"}]),
+                PatchErrorKind::TestFailed {
+                    path: "/baz".to_owned(),
+                },
+            ),
+            (
+                json!([{"op": "add", "path": "/baz/bat", "value": "qux"}]),
+                PatchErrorKind::NotFound {
+                    path: "/baz/bat".to_owned(),
+                },
+            ),
+            (
+                json!([{"op": "remove", "path": "/foo/2"}]),
+                PatchErrorKind::NotFound {
+                    path: "/foo/2".to_owned(),
+                },
+            ),
+            (
+                json!([{"op": "replace", "path": "/foo/01", "value": 1}]),
+                PatchErrorKind::NotFound {
+                    path: "/foo/01".to_owned(),
+                },
+            ),
+            (
+                json!([{"op": "remove", "path": ""}]),
+                PatchErrorKind::RemoveRoot,
+            ),
+            (
+                json!([{"op": "move", "from": "/foo", "path": "/foo/0"}]),
+                PatchErrorKind::MoveIntoDescendant {
+                    from: "/foo".to_owned(),
+                    path: "/foo/0".to_owned(),
+                },
+            ),
+        ] {
+            let mut value = json!({"baz": "qux", "foo": [1, 2]});
+            let mut ops = patch(ops);
+            ops.insert(
+                0,
+                PatchOperation::Add {
+                    path: "/baz".to_owned(),
+                    value: json!("qux"),
+                },
+            );
+            ops.insert(
+                0,
+                PatchOperation::Remove {
+                    path: "/baz".to_owned(),
+                },
+            );
+            let err = apply_patch(&mut value, &ops).unwrap_err();
+            assert_eq!(err.operation(), 2);
+            assert_eq!(err.kind(), &kind);
+            assert_eq!(value, json!({"baz": "qux", "foo": [1, 2]}));
+        }
+    }
+
+    #[test]
+    fn test_compares_numbers_exactly() {
+        let mut value = json!({"id": 9007199254740993_u64});
+        let ops = patch(json!([{"op": "test", "path": "/id", "value": 9007199254740992_u64}]));
+        let err = apply_patch(&mut value, &ops).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &PatchErrorKind::TestFailed {
+                path: "/id".to_owned()
+            }
+        );
+        let ops = patch(json!([{"op": "test", "path": "/id", "value": 9007199254740993_u64}]));
+        apply_patch(&mut value, &ops).unwrap();
+    }
+
+    #[test]
+    fn remove_patch_leaves_out_root() {
+        let value = json!({"a": 1});
+        let remove = JsonPath::parse("$").unwrap().remove_patch(&value);
+        assert!(remove.is_empty());
+        let mut v = value.clone();
+        apply_patch(&mut v, &remove).unwrap();
+        assert_eq!(v, value);
+    }
+
+    #[test]
+    fn query_patches() {
+        let value = json!({"a": [{"b": 1}, {"b": 2}], "c": {"b": 3}});
+        let path = JsonPath::parse("$..b").unwrap();
+        let set = path.set_patch(&value, json!(0));
+        let mut v = value.clone();
+        apply_patch(&mut v, &set).unwrap();
+        assert_eq!(v, json!({"a": [{"b": 0}, {"b": 0}], "c": {"b": 0}}));
+
+        let path = JsonPath::parse("$..[?@.b]").unwrap();
+        let remove = path.remove_patch(&value);
+        assert_eq!(remove.len(), 3);
+        let mut v = value.clone();
+        apply_patch(&mut v, &remove).unwrap();
+        assert_eq!(v, json!({"a": []}));
+
+        let path = JsonPath::parse("$[?@ != 'x'].*").unwrap();
+        let transform = path.transform_patch(&value, |node| json!({"wrapped": node}));
+        let mut v = value.clone();
+        apply_patch(&mut v, &transform).unwrap();
+        assert_eq!(
+            v,
+            json!({"a": [{"wrapped": {"b": 1}}, {"wrapped": {"b": 2}}], "c": {"b": {"wrapped": 3}}})
+        );
+    }
+
+    #[test]
+    fn nested_matches_only_patch_ancestors() {
+        let value = json!({"a": {"a": {"a": 1}}});
+        let path = JsonPath::parse("$..a").unwrap();
+        let remove = path.remove_patch(&value);
+        assert_eq!(
+            remove,
+            [PatchOperation::Remove {
+                path: "/a".to_owned()
+            }]
+        );
+        let set = path.set_patch(&value, json!(null));
+        assert_eq!(set.len(), 1);
+    }
+}
@@ -3,11 +3,24 @@ use std::str::FromStr;
 use serde::{de::Visitor, Deserialize, Serialize};
 use serde_json::Value;
 use serde_json_path_core::{
+    context::{Context, EvalContext, EvaluationError},
     node::{LocatedNodeList, NodeList},
-    spec::query::{Query, Queryable},
+    path::{NormalizedPath, PathElement},
+    spec::{
+        integer::Integer,
+        query::{Query, QueryKind, Queryable},
+        segment::{QuerySegment, QuerySegmentKind, Segment},
+        selector::{index::Index, name::Name, Selector},
+    },
 };
 
-use crate::{parser::parse_query_main, ParseError};
+use crate::{
+    analysis::{analyze, uses_locations, QueryAnalysis},
+    parser::{parse_query_main, parse_relative_query_main},
+    pointer::{array_index, reference_tokens},
+    project::{project, ArrayProjection},
+    JsonPointerError, ParseError,
+};
 
 /// A parsed JSON Path query string
 ///
@@ -58,6 +71,134 @@ impl JsonPath {
         Ok(Self(path))
     }
 
+    /// Create a [`JsonPath`] by parsing a relative JSON Path query string, i.e., one that starts
+    /// with the current node identifier, `@`, instead of the root identifier, `$`
+    ///
+    /// When used to query a [`serde_json::Value`], the value is taken as the current node, so a
+    /// relative query behaves the same as its absolute counterpart. Relative queries are useful
+    /// for describing locations relative to some other node, e.g., one produced by another query.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let path = JsonPath::parse_relative("@.foo[0]")?;
+    /// assert!(path.is_relative());
+    /// let value = json!({"foo": ["bar"]});
+    /// assert_eq!(path.query(&value).all(), vec!["bar"]);
+    /// assert!(JsonPath::parse_relative("$.foo").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_relative(path_str: &str) -> Result<Self, ParseError> {
+        let (_, path) = parse_relative_query_main(path_str).map_err(|err| match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => (path_str, e),
+            nom::Err::Incomplete(_) => unreachable!("we do not use streaming parsers"),
+        })?;
+        Ok(Self(path))
+    }
+
+    /// Create a [`JsonPath`] by parsing a query string written in the legacy JSONPath syntax
+    ///
+    /// This accepts the syntax of the original JSONPath proposal by Stefan Goessner, along with
+    /// the extensions made by Jayway and others, and converts it to an equivalent RFC 9535 query,
+    /// which is produced when the [`JsonPath`] is displayed. In addition to the RFC 9535 syntax,
+    /// this accepts:
+    ///
+    /// - script expressions of the form `[(@.length-N)]`, which are converted to the index `-N`
+    /// - member names that are not valid in dot notation, e.g., `$.2024` or `$.book-title`,
+    ///   which are converted to `['2024']` and `['book-title']`
+    /// - the `=~` operator, where `@.a =~ /pattern/` is converted to `match(@.a, 'pattern')`,
+    ///   and the `i` flag is converted to `(?i)`
+    /// - the `in` and `nin` operators, where `@.a in ['x', 'y']` is converted to
+    ///   `(@.a == 'x' || @.a == 'y')`, and `@.a nin ['x', 'y']` to `(@.a != 'x' && @.a != 'y')`
+    ///
+    /// Legacy syntax with no RFC 9535 equivalent, e.g., other script expressions, functions at the
+    /// end of a path such as `$.book.length()`, or the `anyof` operator, produces an error that
+    /// describes it. The position of any error refers to `path_str`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let path = JsonPath::parse_legacy("$..book[?(@.author =~ /.*tolkien/i)]")?;
+    /// assert_eq!(path.to_string(), "$..book[?(match(@['author'],'(?i).*tolkien'))]");
+    /// let err = JsonPath::parse_legacy("$..book[(@.length/2)]").unwrap_err();
+    /// assert_eq!(err.position(), 8);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "legacy")]
+    pub fn parse_legacy(path_str: &str) -> Result<Self, ParseError> {
+        crate::legacy::parse_legacy_query(path_str).map(Self)
+    }
+
+    /// Check if this [`JsonPath`] is a relative query, i.e., it starts with `@`
+    pub fn is_relative(&self) -> bool {
+        matches!(self.0.kind, QueryKind::Current)
+    }
+
+    /// Convert this [`JsonPath`] into a relative query, i.e., one that starts with `@`
+    ///
+    /// Only the identifier at the start of the query is changed: any absolute queries within
+    /// filter expressions still refer to the root node.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let path = JsonPath::parse("$.foo[?@.bar == $.baz]")?.into_relative();
+    /// assert_eq!(path.to_string(), "@.foo[?@['bar']==$['baz']]");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_relative(mut self) -> Self {
+        self.0.kind = QueryKind::Current;
+        self
+    }
+
+    /// Create a [`JsonPath`] that selects the node at the location of a [`NormalizedPath`]
+    ///
+    /// This produces an absolute query; use [`into_relative`][JsonPath::into_relative] to produce
+    /// a query that locates the node relative to some other node, e.g., one whose location was
+    /// removed using [`NormalizedPath::strip_prefix`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::{JsonPath, NormalizedPath};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let value = json!({"foo": [{"bar": 1}, {"bar": 2}]});
+    /// let base: NormalizedPath = "$['foo'][1]".parse()?;
+    /// let location: NormalizedPath = "$['foo'][1]['bar']".parse()?;
+    /// let relative = location.strip_prefix(&base).expect("base is a prefix");
+    /// let path = JsonPath::from_normalized_path(&relative).into_relative();
+    /// assert_eq!(path.to_string(), "@['bar']");
+    /// let node = base.resolve(&value).expect("base exists");
+    /// assert_eq!(path.query(node).exactly_one()?, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_normalized_path(path: &NormalizedPath<'_>) -> Self {
+        let segments = path
+            .iter()
+            .map(|element| match element {
+                PathElement::Name(name) => Selector::Name(Name::from(name.as_ref())),
+                // an index outside of the I-JSON range can not locate any array element, and
+                // neither can the maximum index, so saturating does not change the result:
+                PathElement::Index(index) => {
+                    Selector::Index(Index(Integer::try_from(*index).unwrap_or(Integer::MAX)))
+                }
+            })
+            .map(child_segment)
+            .collect();
+        Self(Query {
+            kind: QueryKind::Root,
+            segments,
+        })
+    }
+
     /// Query a [`serde_json::Value`] using this [`JsonPath`]
     ///
     /// # Example
@@ -73,9 +214,141 @@ impl JsonPath {
     /// # }
     /// ```
     pub fn query<'b>(&self, value: &'b Value) -> NodeList<'b> {
+        if uses_locations(&self.0) {
+            let ctx = EvalContext::new(value).track_locations();
+            return self
+                .0
+                .query_located_in(value, &ctx, Default::default())
+                .into_iter()
+                .map(|n| n.node())
+                .collect::<Vec<_>>()
+                .into();
+        }
         self.0.query(value, value).into()
     }
 
+    /// Query a [`serde_json::Value`] using this [`JsonPath`], failing if any function fails
+    ///
+    /// Functions can fail by returning a `Result`. When using [`query`][JsonPath::query], a
+    /// failed function produces nothing, whereas this produces an [`EvaluationError`] for the
+    /// first function that fails, along with the location of the node that was being tested.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::JsonPath;
+    /// # use serde_json_path::functions::ValueType;
+    /// #[serde_json_path::function]
+    /// fn parse_int(value: ValueType) -> Result<ValueType, std::num::ParseIntError> {
+    ///     match value.as_value().and_then(|v| v.as_str()) {
+    ///         Some(s) => s.parse::<i64>().map(ValueType::from),
+    ///         None => Ok(ValueType::Nothing),
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let value = json!({"ids": [{"id": "1"}, {"id": "two"}, {"id": "3"}]});
+    /// let path = JsonPath::parse("$.ids[?parse_int(@.id) > 1]")?;
+    /// assert_eq!(path.query(&value).all(), vec![&json!({"id": "3"})]);
+    /// let err = path.try_query(&value).unwrap_err();
+    /// assert_eq!(err.function(), "parse_int");
+    /// assert_eq!(err.location().to_string(), "$['ids'][1]");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_query<'b>(&self, value: &'b Value) -> Result<NodeList<'b>, EvaluationError> {
+        let ctx = EvalContext::new(value).track_locations().strict();
+        let nodes: Vec<&Value> = self
+            .0
+            .query_located_in(value, &ctx, Default::default())
+            .into_iter()
+            .map(|n| n.node())
+            .collect();
+        match ctx.take_error() {
+            Some(err) => Err(err),
+            None => Ok(nodes.into()),
+        }
+    }
+
+    /// Query a [`serde_json::Value`] using this [`JsonPath`], with the variables bound in the
+    /// given [`Context`]
+    ///
+    /// With the `variables` feature, variables are referred to within filter expressions using
+    /// the non-standard `%name` syntax, and can be followed by name and index segments, in the
+    /// same way as a singular query, e.g., `%tenant.limits[0]`. Variables that are not bound in
+    /// `context` produce nothing. The `context` only needs to live for the duration of the
+    /// query, and can be dropped before the produced nodes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::{Context, JsonPath};
+    /// # #[cfg(feature = "variables")]
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let value = json!({"items": [{"price": 5}, {"price": 15}, {"price": 25}]});
+    /// let path = JsonPath::parse("$.items[?@.price > %tenant.limit]")?;
+    /// let context = Context::new().with_var("tenant", json!({"limit": 10}));
+    /// let nodes = path.query_with(&value, &context);
+    /// assert_eq!(nodes.all(), vec![&json!({"price": 15}), &json!({"price": 25})]);
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "variables"))]
+    /// # fn main() {}
+    /// ```
+    pub fn query_with<'b>(&self, value: &'b Value, context: &Context) -> NodeList<'b> {
+        if uses_locations(&self.0) {
+            return self.query_with_context(value, context);
+        }
+        self.0
+            .query_in(value, &EvalContext::with_context(value, context))
+            .into()
+    }
+
+    /// Query a [`serde_json::Value`] using this [`JsonPath`], making the given [`Context`], and
+    /// the location of each node tested by a filter, available to functions
+    ///
+    /// Functions declared with a [`FunctionContext`] parameter can use it to access the root
+    /// node, the location of the current node, and any application data held in `context`. Since
+    /// this computes the location of every node that is tested by a filter, it is slower than
+    /// [`query`][JsonPath::query]. As with [`query_with`][JsonPath::query_with], the `context`
+    /// can be dropped before the produced nodes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::{Context, JsonPath};
+    /// # use serde_json_path::functions::{FunctionContext, LogicalType};
+    /// struct Hidden(Vec<String>);
+    ///
+    /// #[serde_json_path::function]
+    /// fn visible(ctx: &FunctionContext) -> LogicalType {
+    ///     match (ctx.location(), ctx.data::<Hidden>()) {
+    ///         (Some(loc), Some(Hidden(hidden))) => (!hidden.contains(&loc.to_string())).into(),
+    ///         _ => LogicalType::True,
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let value = json!({"users": ["alice", "bob", "carol"]});
+    /// let path = JsonPath::parse("$.users[?visible()]")?;
+    /// let context = Context::new().with_data(Hidden(vec!["$['users'][1]".to_owned()]));
+    /// let nodes = path.query_with_context(&value, &context);
+    /// assert_eq!(nodes.all(), vec!["alice", "carol"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`FunctionContext`]: crate::functions::FunctionContext
+    pub fn query_with_context<'b>(&self, value: &'b Value, context: &Context) -> NodeList<'b> {
+        let ctx = EvalContext::with_context(value, context).track_locations();
+        self.0
+            .query_located_in(value, &ctx, Default::default())
+            .into_iter()
+            .map(|n| n.node())
+            .collect::<Vec<_>>()
+            .into()
+    }
+
     /// Query a [`serde_json::Value`] using this [`JsonPath`] to produce a [`LocatedNodeList`]
     ///
     /// # Example
@@ -97,10 +370,157 @@ impl JsonPath {
     /// # }
     /// ```
     pub fn query_located<'b>(&self, value: &'b Value) -> LocatedNodeList<'b> {
+        let mut ctx = EvalContext::new(value);
+        if uses_locations(&self.0) {
+            ctx = ctx.track_locations();
+        }
         self.0
-            .query_located(value, value, Default::default())
+            .query_located_in(value, &ctx, Default::default())
             .into()
     }
+
+    /// Project a [`serde_json::Value`] onto the nodes matched by this [`JsonPath`]
+    ///
+    /// This produces a new value with the same structure as `value`, but only containing the
+    /// matched nodes and their ancestors. Arrays are compacted, i.e., only their matched elements
+    /// are kept; use the [`project`][crate::project] function to project onto several queries, or
+    /// to keep the original array indices.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let value = json!({"foo": [{"bar": 1, "baz": 2}, {"bar": 3, "baz": 4}], "bop": 5});
+    /// let path = JsonPath::parse("$.foo[*].bar")?;
+    /// assert_eq!(path.project(&value), json!({"foo": [{"bar": 1}, {"bar": 3}]}));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn project(&self, value: &Value) -> Value {
+        project([self], value, ArrayProjection::Compact)
+    }
+
+    /// Statically analyze this [`JsonPath`] without evaluating it
+    ///
+    /// This can be used to inspect properties of a query, such as whether it is singular, or an
+    /// upper bound on how expensive it is to evaluate, before running it against a document.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let path = JsonPath::parse("$..foo[?@.bar..baz]")?;
+    /// let analysis = path.analyze();
+    /// assert_eq!(analysis.descendant_segments(), 2);
+    /// assert_eq!(analysis.cost().degree(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn analyze(&self) -> QueryAnalysis {
+        analyze(&self.0)
+    }
+
+    /// Check if this [`JsonPath`] will produce at most a single node
+    ///
+    /// A query is singular if it contains no descendant segments, and each of its segments is
+    /// composed of a single name or index selector.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// assert!(JsonPath::parse("$.foo[0]")?.is_singular());
+    /// assert!(!JsonPath::parse("$.foo[0, 1]")?.is_singular());
+    /// assert!(!JsonPath::parse("$..foo")?.is_singular());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_singular(&self) -> bool {
+        self.0.is_singular()
+    }
+
+    /// Convert this [`JsonPath`] to a [JSON Pointer][json-pointer] string, without evaluating it
+    ///
+    /// This produces `None` unless the query is composed only of name selectors and non-negative
+    /// index selectors, i.e., it addresses the same location in any document it is applied to.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::ParseError> {
+    /// let path = JsonPath::parse("$.foo['bar/baz'][1]")?;
+    /// assert_eq!(path.to_json_pointer().as_deref(), Some("/foo/bar~1baz/1"));
+    /// let path = JsonPath::parse("$.foo[-1]")?;
+    /// assert_eq!(path.to_json_pointer(), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [json-pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+    pub fn to_json_pointer(&self) -> Option<String> {
+        self.0
+            .segments
+            .iter()
+            .map(|s| match (&s.kind, &s.segment) {
+                (QuerySegmentKind::Descendant, _) => None,
+                (_, Segment::DotName(name)) => Some(PathElement::from(name)),
+                (_, Segment::LongHand(selectors)) => match selectors.as_slice() {
+                    [Selector::Name(name)] => Some(PathElement::from(name.as_str())),
+                    [Selector::Index(Index(index))] => {
+                        usize::try_from(*index).ok().map(PathElement::from)
+                    }
+                    _ => None,
+                },
+                (_, Segment::Wildcard | Segment::Parent) => None,
+            })
+            .collect::<Option<NormalizedPath>>()
+            .map(|path| path.to_json_pointer())
+    }
+
+    /// Create a [`JsonPath`] from a [JSON Pointer][json-pointer] string
+    ///
+    /// Each reference token in the pointer is converted to a name selector, except for those that
+    /// represent an array index, i.e., `0` or a sequence of digits without a leading zero, which
+    /// are converted to index selectors. Since JSONPath does not have a selector that matches both
+    /// an object member and an array element, the produced query will not select object members
+    /// whose names are array indices, e.g., `{"0": true}`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::JsonPath;
+    /// # fn main() -> Result<(), serde_json_path::JsonPointerError> {
+    /// let path = JsonPath::from_json_pointer("/foo/bar~1baz/1")?;
+    /// assert_eq!(path.to_string(), "$['foo']['bar/baz'][1]");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [json-pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+    pub fn from_json_pointer(pointer: &str) -> Result<Self, JsonPointerError> {
+        let segments = reference_tokens(pointer)?
+            .into_iter()
+            .map(
+                |token| match array_index(&token).and_then(|i| Integer::try_from(i).ok()) {
+                    Some(index) => Selector::Index(Index(index)),
+                    None => Selector::Name(Name(token)),
+                },
+            )
+            .map(child_segment)
+            .collect();
+        Ok(Self(Query {
+            kind: QueryKind::Root,
+            segments,
+        }))
+    }
+}
+
+/// Produce a child segment containing the single `selector`
+fn child_segment(selector: Selector) -> QuerySegment {
+    QuerySegment {
+        kind: QuerySegmentKind::Child,
+        segment: Segment::LongHand(vec![selector]),
+    }
 }
 
 impl FromStr for JsonPath {
@@ -144,7 +564,12 @@ impl<'de> Deserialize<'de> for JsonPath {
             where
                 E: serde::de::Error,
             {
-                JsonPath::parse(v).map_err(serde::de::Error::custom)
+                // relative queries are accepted so that they can be round-tripped:
+                if v.starts_with('@') {
+                    JsonPath::parse_relative(v).map_err(serde::de::Error::custom)
+                } else {
+                    JsonPath::parse(v).map_err(serde::de::Error::custom)
+                }
             }
         }
 
@@ -156,7 +581,7 @@ impl<'de> Deserialize<'de> for JsonPath {
 mod tests {
     use serde_json::{from_value, json, to_value};
 
-    use crate::JsonPath;
+    use crate::{JsonPath, NormalizedPath, PathElement};
 
     #[test]
     fn test_send() {
@@ -179,4 +604,269 @@ mod tests {
             .expect("round trip");
         assert_eq!(p1, p2);
     }
+
+    #[test]
+    fn json_pointer_round_trip() {
+        for (pointer, query) in [
+            ("", "$"),
+            ("/", "$['']"),
+            ("/foo/0/bar", "$['foo'][0]['bar']"),
+            ("/a~1b/m~0n/01/-", "$['a/b']['m~n']['01']['-']"),
+        ] {
+            let path = JsonPath::from_json_pointer(pointer).expect("valid pointer");
+            assert_eq!(path.to_string(), query);
+            assert!(path.is_singular());
+            assert_eq!(path.to_json_pointer().as_deref(), Some(pointer));
+        }
+    }
+
+    #[test]
+    fn to_json_pointer_non_convertible() {
+        for q in ["$.*", "$..foo", "$[-1]", "$['a','b']", "$[0:1]", "$[?@.a]"] {
+            let path = JsonPath::parse(q).expect("valid query");
+            assert!(path.to_json_pointer().is_none(), "{q}");
+        }
+    }
+
+    #[test]
+    fn relative_paths() {
+        let path = JsonPath::parse_relative("@['a'][0]").expect("valid relative query");
+        assert!(path.is_relative());
+        let np: NormalizedPath = "$['a'][0]".parse().unwrap();
+        assert_eq!(JsonPath::from_normalized_path(&np).into_relative(), path);
+        let de = to_value(&path)
+            .and_then(from_value::<JsonPath>)
+            .expect("round trip");
+        assert_eq!(path, de);
+        assert!(!JsonPath::parse("$['a']").unwrap().is_relative());
+        assert!(JsonPath::parse("@['a']").is_err());
+    }
+
+    #[test]
+    fn from_normalized_path() {
+        let value = json!({"a": [{"b'c": 1}, {"b'c": 2}]});
+        let path = JsonPath::parse("$.a[*]['b\\'c']").unwrap();
+        for loc in path.query_located(&value).locations() {
+            let exact = JsonPath::from_normalized_path(loc);
+            assert_eq!(exact.to_string(), loc.to_string());
+            assert_eq!(
+                exact
+                    .query_located(&value)
+                    .exactly_one()
+                    .unwrap()
+                    .location(),
+                loc
+            );
+        }
+        let huge = NormalizedPath::from(vec![PathElement::Index(usize::MAX)]);
+        assert!(JsonPath::from_normalized_path(&huge)
+            .query(&json!([1]))
+            .is_empty());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for q in ["$..foo", "$..*", "$..['foo',0]", "$.foo..bar[?@..baz]"] {
+            let p1 = JsonPath::parse(q).expect("valid query");
+            let p2 = JsonPath::parse(&p1.to_string()).expect("round trip");
+            assert_eq!(p1, p2, "{q}");
+        }
+    }
+
+    #[cfg(feature = "variables")]
+    #[test]
+    fn query_with_variables() {
+        use crate::Context;
+
+        let value = json!([
+            {"name": "a", "price": 5, "tags": ["x"]},
+            {"name": "b", "price": 15, "tags": ["y"]},
+        ]);
+        let context = Context::new()
+            .with_var("limit", 10)
+            .with_var("doc", json!({"names": ["b"], "tag": "x"}));
+        for (q, exp) in [
+            ("$[?@.price > %limit].name", vec!["b"]),
+            ("$[?@.price <= %limit].name", vec!["a"]),
+            ("$[?@.name == %doc.names[0]].name", vec!["b"]),
+            ("$[?@.tags[0] == %doc['tag']].name", vec!["a"]),
+            ("$[?length(%doc.names) == 1].name", vec!["a", "b"]),
+            ("$[?@.price > %missing].name", vec![]),
+            ("$[?%missing == %also_missing].name", vec!["a", "b"]),
+        ] {
+            let path = JsonPath::parse(q).expect("valid query");
+            assert_eq!(path.query_with(&value, &context).all(), exp, "{q}");
+            let round_trip = JsonPath::parse(&path.to_string()).expect("round trip");
+            assert_eq!(path, round_trip, "{q}");
+        }
+        // without a context, variables produce nothing:
+        let path = JsonPath::parse("$[?@.price > %limit]").unwrap();
+        assert!(path.query(&value).is_empty());
+    }
+
+    #[test]
+    fn query_with_context_dropped_before_nodes() {
+        use crate::Context;
+
+        let value = json!({"a": [1, 2]});
+        let path = JsonPath::parse("$.a[*]").unwrap();
+        let nodes = {
+            let context = Context::new().with_var("limit", 1);
+            path.query_with(&value, &context)
+        };
+        assert_eq!(nodes.all(), vec![1, 2]);
+    }
+
+    #[cfg(feature = "parent-selector")]
+    #[test]
+    fn parent_selector() {
+        use serde_json_path_core::spec::query::Queryable;
+
+        let value = json!({
+            "orders": [
+                {"id": 1, "lines": [{"sku": "A"}, {"sku": "B"}]},
+                {"id": 2, "lines": [{"sku": "B"}, {"sku": "B"}]},
+                {"id": 3, "lines": [{"sku": "C"}]},
+            ]
+        });
+        for (q, exp) in [
+            (
+                "$.orders[*].lines[?@.sku == 'B']^^.id",
+                vec![json!(1), json!(2)],
+            ),
+            ("$..[?@.sku == 'B']^^.id", vec![json!(1), json!(2)]),
+            ("$.orders[2].lines[0]^^^^", vec![value.clone()]),
+            ("$^", vec![]),
+            ("$.orders[*].lines[?@^^[?@ == 3]].sku", vec![json!("C")]),
+            (
+                "$.orders[?@.lines[?count(@^[*]) > 1]].id",
+                vec![json!(1), json!(2)],
+            ),
+            (
+                "$.orders[*]['id', 'lines']^.id",
+                vec![json!(1), json!(2), json!(3)],
+            ),
+        ] {
+            let path = JsonPath::parse(q).expect("valid query");
+            let nodes: Vec<serde_json::Value> = path.query(&value).into_iter().cloned().collect();
+            assert_eq!(nodes, exp, "{q}");
+            let located = path.query_located(&value);
+            assert_eq!(located.nodes().cloned().collect::<Vec<_>>(), exp, "{q}");
+            let round_trip = JsonPath::parse(&path.to_string()).expect("round trip");
+            assert_eq!(path, round_trip, "{q}");
+        }
+        let path = JsonPath::parse_relative("@.orders[0].lines^").unwrap();
+        assert_eq!(
+            path.query(&value).exactly_one().unwrap(),
+            &value["orders"][0]
+        );
+        assert!(!JsonPath::parse("$.orders^").unwrap().is_singular());
+        assert!(JsonPath::parse("$..^").is_err());
+        // parent segments can not be used in singular queries, so can not be compared:
+        assert!(JsonPath::parse("$.orders[*].lines[?@^^.id == 3]").is_err());
+        assert!(JsonPath::parse("$..[?@^.id]").is_ok());
+        assert!(JsonPath::parse("$..[?count(@^.*) > 1]").is_ok());
+        // without tracked locations, a relative query can only find the parents of nodes within
+        // the root node, when it starts there:
+        let query = &JsonPath::parse_relative("@.orders[1].lines[0]^^.id")
+            .unwrap()
+            .0;
+        assert_eq!(query.query(&value, &value), vec![&json!(2)]);
+        let query = &JsonPath::parse_relative("@^^.id").unwrap().0;
+        assert!(query
+            .query(&value["orders"][1]["lines"][0], &value)
+            .is_empty());
+    }
+
+    #[cfg(feature = "arithmetic")]
+    #[test]
+    fn arithmetic() {
+        let value = json!([
+            {"id": 1, "price": 10, "qty": 20, "start": 0, "end": 7200},
+            {"id": 2, "price": 2.5, "qty": 4, "start": 100, "end": 200},
+            {"id": 3, "price": "9", "qty": 100, "start": 0},
+            {"id": 4, "price": 9007199254740993_u64, "qty": 1, "start": 0, "end": 0},
+        ]);
+        for (q, exp) in [
+            ("$[?@.price * @.qty > 100].id", vec![1, 4]),
+            ("$[?@.end - @.start > 3600].id", vec![1]),
+            ("$[?@.price * @.qty == 10].id", vec![2]),
+            ("$[?@.id % 2 == 0].id", vec![2, 4]),
+            ("$[?@.qty / @.id == 20].id", vec![1]),
+            ("$[?@.qty / 0 == @.qty / 0].id", vec![1, 2, 3, 4]),
+            ("$[?1 + @.id * 2 == 7].id", vec![3]),
+            ("$[?(1 + @.id) * 2 == 8].id", vec![3]),
+            ("$[?@.id - 1 - 1 == 0].id", vec![2]),
+            ("$[?@.id - (1 - 1) == 1].id", vec![1]),
+            ("$[?(@.price + 0 == 10) || @.id == 2].id", vec![1, 2]),
+            ("$[?length(@) - 4 > 0].id", vec![1, 2, 4]),
+            ("$[?@.price - 1 == 9007199254740992].id", vec![4]),
+            // operands that are not numbers produce nothing:
+            ("$[?@.price + 1 == 10].id", vec![]),
+            ("$[?@.price + 1 >= 0].id", vec![1, 2, 4]),
+        ] {
+            let path = JsonPath::parse(q).expect("valid query");
+            let nodes: Vec<serde_json::Value> = path.query(&value).into_iter().cloned().collect();
+            let exp: Vec<serde_json::Value> = exp.into_iter().map(Into::into).collect();
+            assert_eq!(nodes, exp, "{q}");
+            let round_trip = JsonPath::parse(&path.to_string()).expect("round trip");
+            assert_eq!(path, round_trip, "{q}");
+        }
+        for (q, display) in [
+            ("$[?(@.a + 1) * 2 > 0]", "$[?(@['a']+1)*2>0]"),
+            ("$[?@.a - (@.b - 1) > 0]", "$[?@['a']-(@['b']-1)>0]"),
+            ("$[?(@.a * 2) + 1 > 0]", "$[?@['a']*2+1>0]"),
+            ("$[?@.a / (2 * @.b) > 0]", "$[?@['a']/(2*@['b'])>0]"),
+            ("$[?(length(@.a) == 1)]", "$[?(length(@['a'])==1)]"),
+            ("$[?(@.a) == 1]", "$[?@['a']==1]"),
+            ("$[?@.a-1 == -1]", "$[?@['a']-1==-1]"),
+        ] {
+            assert_eq!(JsonPath::parse(q).unwrap().to_string(), display);
+        }
+        assert!(JsonPath::parse("$[?@.a + > 1]").is_err());
+        assert!(JsonPath::parse("$[?@.* + 1 > 1]").is_err());
+        assert!(JsonPath::parse("$[?@.a + 1]").is_err());
+    }
+
+    #[cfg(feature = "membership")]
+    #[test]
+    fn membership() {
+        let value = json!([
+            {"id": 1, "status": "open", "tags": ["x", "y"], "level": 1},
+            {"id": 2, "status": "pending", "tags": ["y"], "level": 2.0},
+            {"id": 3, "status": "closed", "tags": [], "level": 2.5},
+            {"id": 4, "tags": "x", "level": null},
+        ]);
+        for (q, exp) in [
+            ("$[?@.status in ['open', 'pending']].id", vec![1, 2]),
+            ("$[?@.status nin ['open', 'pending']].id", vec![3, 4]),
+            ("$[?@.tags contains 'x'].id", vec![1]),
+            ("$[?!(@.tags contains 'y')].id", vec![3, 4]),
+            ("$[?@.level in [2, 3]].id", vec![2]),
+            ("$[?@.level in [1.0, 2.5]].id", vec![1, 3]),
+            ("$[?@.level in [null, true]].id", vec![4]),
+            ("$[?@.status in []].id", vec![]),
+            ("$[?@.id in $[0].tags].id", vec![]),
+            ("$[?'y' in @.tags && @.id > 1].id", vec![2]),
+            ("$[?[1, 3] contains @.id].id", vec![1, 3]),
+            ("$[?@.tags contains $[0].tags[1]].id", vec![1, 2]),
+        ] {
+            let path = JsonPath::parse(q).expect("valid query");
+            let nodes: Vec<serde_json::Value> = path.query(&value).into_iter().cloned().collect();
+            let exp: Vec<serde_json::Value> = exp.into_iter().map(Into::into).collect();
+            assert_eq!(nodes, exp, "{q}");
+            let round_trip = JsonPath::parse(&path.to_string()).expect("round trip");
+            assert_eq!(path, round_trip, "{q}");
+        }
+        assert_eq!(
+            JsonPath::parse("$[?@.status in [ 'open' ,'pending' ]]")
+                .unwrap()
+                .to_string(),
+            "$[?@['status'] in ['open','pending']]"
+        );
+        assert!(JsonPath::parse("$[?@.status in ['open',]]").is_err());
+        assert!(JsonPath::parse("$[?@.status in [@.a]]").is_err());
+        assert!(JsonPath::parse("$[?@.status in]").is_err());
+        assert!(JsonPath::parse("$[?@.tags in [['x']]]").is_err());
+    }
 }
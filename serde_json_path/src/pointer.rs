@@ -0,0 +1,78 @@
+//! Helpers for working with [JSON Pointers][json-pointer]
+//!
+//! [json-pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+use crate::JsonPointerError;
+
+/// Split a JSON Pointer into its reference tokens, with `~1` and `~0` escape sequences decoded
+pub(crate) fn reference_tokens(pointer: &str) -> Result<Vec<String>, JsonPointerError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(JsonPointerError::MissingLeadingSlash);
+    };
+    let mut tokens = vec![String::new()];
+    let mut chars = rest.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let token = tokens.last_mut().expect("tokens is not empty");
+        match c {
+            '/' => tokens.push(String::new()),
+            '~' => match chars.next() {
+                Some((_, '0')) => token.push('~'),
+                Some((_, '1')) => token.push('/'),
+                // account for the stripped leading '/', and make the position 1-indexed:
+                _ => return Err(JsonPointerError::InvalidEscape { position: i + 2 }),
+            },
+            c => token.push(c),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Interpret a reference token as an array index
+///
+/// As per RFC 6901, an array index is either `0`, or a sequence of digits without a leading zero.
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    let valid = match token.as_bytes() {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    };
+    valid.then(|| token.parse().ok()).flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{array_index, reference_tokens};
+    use crate::JsonPointerError;
+
+    #[test]
+    fn tokens() {
+        assert_eq!(reference_tokens("").unwrap(), Vec::<String>::new());
+        assert_eq!(reference_tokens("/").unwrap(), [""]);
+        assert_eq!(reference_tokens("/foo/0").unwrap(), ["foo", "0"]);
+        assert_eq!(
+            reference_tokens("/a~1b/m~0n/~01").unwrap(),
+            ["a/b", "m~n", "~1"]
+        );
+        assert_eq!(
+            reference_tokens("foo"),
+            Err(JsonPointerError::MissingLeadingSlash)
+        );
+        assert_eq!(
+            reference_tokens("/foo/b~2"),
+            Err(JsonPointerError::InvalidEscape { position: 7 })
+        );
+    }
+
+    #[test]
+    fn indices() {
+        assert_eq!(array_index("0"), Some(0));
+        assert_eq!(array_index("42"), Some(42));
+        assert_eq!(array_index("042"), None);
+        assert_eq!(array_index("-"), None);
+        assert_eq!(array_index("-1"), None);
+        assert_eq!(array_index("1e3"), None);
+        assert_eq!(array_index(""), None);
+    }
+}
@@ -0,0 +1,200 @@
+//! Projection of JSON values onto the nodes matched by JSONPath queries
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value};
+use serde_json_path_core::path::{NormalizedPath, PathElement};
+
+use crate::JsonPath;
+
+/// The policy for projecting arrays that have some, but not all, of their elements matched
+///
+/// This is used by the [`project`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayProjection {
+    /// Only keep the matched elements, in their original order
+    ///
+    /// The indices of the elements in the projected array may differ from those in the original.
+    #[default]
+    Compact,
+    /// Keep the matched elements at their original indices, and fill the gaps with `null`
+    ///
+    /// Unmatched elements that come after the last matched element are dropped.
+    Pad,
+}
+
+/// Project a [`serde_json::Value`] onto the nodes matched by a set of [`JsonPath`]s
+///
+/// This produces a new value with the same structure as `value`, but only containing the
+/// matched nodes, along with their ancestors. The ancestors only contain the members, or
+/// elements, that lead to a matched node; the matched nodes themselves are kept in their
+/// entirety. If none of the `paths` match any node, this produces `null`.
+///
+/// See [`JsonPath::project`] for projecting onto a single query.
+///
+/// # Example
+/// ```rust
+/// # use serde_json::json;
+/// # use serde_json_path::{project, ArrayProjection, JsonPath};
+/// # fn main() -> Result<(), serde_json_path::ParseError> {
+/// let value = json!({
+///     "user": {
+///         "name": "Alice",
+///         "password": "hunter2",
+///         "emails": ["alice@example.com", "alice@work.example.com"]
+///     }
+/// });
+/// let paths = [
+///     JsonPath::parse("$.user.name")?,
+///     JsonPath::parse("$.user.emails[1]")?,
+/// ];
+/// assert_eq!(
+///     project(&paths, &value, ArrayProjection::Compact),
+///     json!({"user": {"name": "Alice", "emails": ["alice@work.example.com"]}}),
+/// );
+/// assert_eq!(
+///     project(&paths, &value, ArrayProjection::Pad),
+///     json!({"user": {"name": "Alice", "emails": [null, "alice@work.example.com"]}}),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn project<'p, I>(paths: I, value: &Value, arrays: ArrayProjection) -> Value
+where
+    I: IntoIterator<Item = &'p JsonPath>,
+{
+    let locations: Vec<NormalizedPath<'_>> = paths
+        .into_iter()
+        .flat_map(|path| {
+            path.query_located(value)
+                .into_iter()
+                .map(NormalizedPath::from)
+        })
+        .collect();
+    if locations.is_empty() {
+        return Value::Null;
+    }
+    let suffixes: Vec<&[PathElement<'_>]> = locations
+        .iter()
+        .map(|loc| loc.get(..).unwrap_or_default())
+        .collect();
+    project_node(value, &suffixes, arrays)
+}
+
+/// Project `value` onto the nodes located by `suffixes`, which are relative to `value`
+fn project_node(value: &Value, suffixes: &[&[PathElement<'_>]], arrays: ArrayProjection) -> Value {
+    if suffixes.iter().any(|s| s.is_empty()) {
+        return value.clone();
+    }
+    match value {
+        Value::Object(map) => {
+            let mut children: HashMap<&str, Vec<&[PathElement<'_>]>> = HashMap::new();
+            for (first, rest) in suffixes.iter().filter_map(|s| s.split_first()) {
+                if let Some(name) = first.as_name() {
+                    children.entry(name).or_default().push(rest);
+                }
+            }
+            // iterate over the original map, so that its ordering is preserved:
+            let projected: Map<String, Value> = map
+                .iter()
+                .filter_map(|(k, v)| {
+                    children
+                        .get(k.as_str())
+                        .map(|s| (k.to_owned(), project_node(v, s, arrays)))
+                })
+                .collect();
+            Value::Object(projected)
+        }
+        Value::Array(list) => {
+            let mut children: BTreeMap<usize, Vec<&[PathElement<'_>]>> = BTreeMap::new();
+            for (first, rest) in suffixes.iter().filter_map(|s| s.split_first()) {
+                if let Some(index) = first.as_index() {
+                    children.entry(index).or_default().push(rest);
+                }
+            }
+            let mut projected = Vec::new();
+            for (index, s) in children {
+                let Some(v) = list.get(index) else {
+                    continue;
+                };
+                if arrays == ArrayProjection::Pad {
+                    projected.resize(index, Value::Null);
+                }
+                projected.push(project_node(v, &s, arrays));
+            }
+            Value::Array(projected)
+        }
+        // locations produced by a query always resolve, so a primitive value can only be reached
+        // by an empty suffix, which is handled above:
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{project, ArrayProjection};
+    use crate::JsonPath;
+
+    fn paths(queries: &[&str]) -> Vec<JsonPath> {
+        queries
+            .iter()
+            .map(|q| JsonPath::parse(q).expect("valid query"))
+            .collect()
+    }
+
+    #[test]
+    fn project_objects() {
+        let value = json!({"a": {"b": 1, "c": 2}, "d": [1, 2], "e": null});
+        for (queries, exp) in [
+            (vec!["$"], value.clone()),
+            (vec!["$.a"], json!({"a": {"b": 1, "c": 2}})),
+            (vec!["$.a.b", "$.e"], json!({"a": {"b": 1}, "e": null})),
+            (vec!["$.a", "$.a.b"], json!({"a": {"b": 1, "c": 2}})),
+            (vec!["$..b"], json!({"a": {"b": 1}})),
+            (vec!["$.x"], json!(null)),
+            (vec![], json!(null)),
+        ] {
+            assert_eq!(
+                project(&paths(&queries), &value, ArrayProjection::Compact),
+                exp,
+                "{queries:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn project_arrays() {
+        let value = json!([{"a": 1, "b": 2}, 3, {"a": 4, "b": 5}, 6]);
+        for (queries, compact, pad) in [
+            (
+                vec!["$[*].a"],
+                json!([{"a": 1}, {"a": 4}]),
+                json!([{"a": 1}, null, {"a": 4}]),
+            ),
+            (
+                vec!["$[-1]", "$[1]"],
+                json!([3, 6]),
+                json!([null, 3, null, 6]),
+            ),
+            (
+                vec!["$[2].b", "$[2].a"],
+                json!([{"a": 4, "b": 5}]),
+                json!([null, null, {"a": 4, "b": 5}]),
+            ),
+            (vec!["$[?@ == 3]", "$[1]"], json!([3]), json!([null, 3])),
+        ] {
+            let paths = paths(&queries);
+            assert_eq!(
+                project(&paths, &value, ArrayProjection::Compact),
+                compact,
+                "{queries:?}"
+            );
+            assert_eq!(
+                project(&paths, &value, ArrayProjection::Pad),
+                pad,
+                "{queries:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Redaction of the nodes matched by JSONPath queries
+use std::cmp::Ordering;
+
+use serde_json::Value;
+use serde_json_path_core::path::NormalizedPath;
+
+use crate::JsonPath;
+
+/// The strategy used by a [`Redactor`] to redact a matched node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Redaction {
+    /// Replace the node with a fixed value, e.g., `"[REDACTED]"`
+    Replace(Value),
+    /// Replace the node with a hash of its JSON representation, as a hexadecimal string
+    ///
+    /// This uses the 64-bit [FNV-1a][fnv] hash, which is deterministic, so the same value always
+    /// produces the same hash. This allows redacted values to be correlated, e.g., across log
+    /// lines, without revealing them.
+    ///
+    /// Note that this is **not** a cryptographic hash: values drawn from a small set, such as
+    /// phone numbers, could be recovered by hashing every possible value.
+    ///
+    /// [fnv]: https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function
+    Hash,
+    /// Keep the last `N` characters of a string or number, and replace the others with `*`
+    ///
+    /// Strings or numbers that are not longer than `N` characters are masked entirely, and
+    /// nodes of any other type are replaced with `null`. The redacted node is always a string.
+    KeepLast(usize),
+    /// Remove the node from its parent object or array
+    ///
+    /// If the root node is matched, it is replaced with `null`.
+    Remove,
+}
+
+impl Redaction {
+    fn apply(&self, node: &mut Value) {
+        *node = match self {
+            Redaction::Replace(v) => v.clone(),
+            Redaction::Hash => Value::String(format!("{:016x}", fnv1a(&node.to_string()))),
+            Redaction::KeepLast(n) => {
+                let s = match node {
+                    Value::String(s) => std::mem::take(s),
+                    Value::Number(n) => n.to_string(),
+                    _ => {
+                        *node = Value::Null;
+                        return;
+                    }
+                };
+                let len = s.chars().count();
+                let keep = if len > *n { *n } else { 0 };
+                let masked = "*".repeat(len - keep);
+                Value::String(masked + &s.chars().skip(len - keep).collect::<String>())
+            }
+            Redaction::Remove => Value::Null,
+        };
+    }
+}
+
+/// Redacts the nodes matched by a set of [`JsonPath`]s from a [`serde_json::Value`]
+///
+/// Each [`JsonPath`] is paired with the [`Redaction`] strategy used for the nodes that it
+/// matches. All of the queries are evaluated against the original value before any node is
+/// redacted, so the result does not depend on the order in which redactions are applied.
+///
+/// If a node is matched by more than one query, it is redacted using the strategy of the query
+/// that was added first. If both a node and one of its ancestors are matched, only the
+/// ancestor is redacted.
+///
+/// # Example
+/// ```rust
+/// # use serde_json::json;
+/// # use serde_json_path::{JsonPath, Redaction, Redactor};
+/// # fn main() -> Result<(), serde_json_path::ParseError> {
+/// let redactor = Redactor::new()
+///     .with(JsonPath::parse("$..password")?, Redaction::Remove)
+///     .with(JsonPath::parse("$..[?@.type == 'ssn'].value")?, Redaction::Replace("***".into()))
+///     .with(JsonPath::parse("$.cards[*].number")?, Redaction::KeepLast(4));
+/// let mut value = json!({
+///     "user": "alice",
+///     "password": "hunter2",
+///     "ids": [{"type": "ssn", "value": "078-05-1120"}],
+///     "cards": [{"number": "4111111111111111"}]
+/// });
+/// let report = redactor.redact_with_report(&mut value);
+/// assert_eq!(value, json!({
+///     "user": "alice",
+///     "ids": [{"type": "ssn", "value": "***"}],
+///     "cards": [{"number": "************1111"}]
+/// }));
+/// let locations: Vec<String> = report.iter().map(ToString::to_string).collect();
+/// assert_eq!(locations, [
+///     "$['cards'][0]['number']",
+///     "$['ids'][0]['value']",
+///     "$['password']",
+/// ]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    rules: Vec<(JsonPath, Redaction)>,
+}
+
+impl Redactor {
+    /// Create a new [`Redactor`] without any queries
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a [`JsonPath`] whose matched nodes will be redacted using the given [`Redaction`]
+    pub fn with(mut self, path: JsonPath, redaction: Redaction) -> Self {
+        self.add(path, redaction);
+        self
+    }
+
+    /// Add a [`JsonPath`] whose matched nodes will be redacted using the given [`Redaction`]
+    pub fn add(&mut self, path: JsonPath, redaction: Redaction) -> &mut Self {
+        self.rules.push((path, redaction));
+        self
+    }
+
+    /// Redact the matched nodes in `value`
+    pub fn redact(&self, value: &mut Value) {
+        self.redact_with_report(value);
+    }
+
+    /// Redact the matched nodes in `value`, and produce the locations of the redacted nodes, in
+    /// document order
+    pub fn redact_with_report(&self, value: &mut Value) -> Vec<NormalizedPath<'static>> {
+        let mut matches: Vec<(NormalizedPath<'static>, &Redaction)> = self
+            .rules
+            .iter()
+            .flat_map(|(path, redaction)| {
+                path.query_located(value)
+                    .into_iter()
+                    .map(move |node| (NormalizedPath::from(node).into_owned(), redaction))
+            })
+            .collect();
+        // the sort is stable, so when de-duplicating, the strategy from the first rule is kept:
+        matches.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        matches.dedup_by(|(a, _), (b, _)| a == b);
+        // in document order, descendants immediately follow their ancestors:
+        let mut redactions: Vec<(NormalizedPath<'static>, &Redaction)> = Vec::new();
+        for (path, redaction) in matches {
+            if !redactions.last().is_some_and(|(p, _)| path.starts_with(p)) {
+                redactions.push((path, redaction));
+            }
+        }
+        // apply in reverse document order, so that removing array elements does not change the
+        // location of any node that is yet to be redacted:
+        for (path, redaction) in redactions.iter().rev() {
+            match redaction {
+                Redaction::Remove if !path.is_empty() => {
+                    path.remove(value)
+                        .expect("location of matched node is valid");
+                }
+                _ => redaction.apply(
+                    path.resolve_mut(value)
+                        .expect("location of matched node is valid"),
+                ),
+            }
+        }
+        redactions.into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+/// The 64-bit FNV-1a hash
+fn fnv1a(s: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    s.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{fnv1a, Redaction, Redactor};
+    use crate::JsonPath;
+
+    fn redactor(rules: &[(&str, Redaction)]) -> Redactor {
+        rules.iter().fold(Redactor::new(), |r, (q, redaction)| {
+            r.with(JsonPath::parse(q).expect("valid query"), redaction.clone())
+        })
+    }
+
+    #[test]
+    fn fnv1a_known_values() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a("foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn strategies() {
+        let value = json!({"s": "secret", "n": 123456, "b": true, "short": "ab"});
+        for (redaction, exp) in [
+            (
+                Redaction::Replace(json!("[REDACTED]")),
+                json!({"s": "[REDACTED]", "n": "[REDACTED]", "b": "[REDACTED]", "short": "[REDACTED]"}),
+            ),
+            (
+                Redaction::KeepLast(2),
+                json!({"s": "****et", "n": "****56", "b": null, "short": "**"}),
+            ),
+            (Redaction::Remove, json!({})),
+            (
+                Redaction::Hash,
+                json!({
+                    "s": format!("{:016x}", fnv1a("\"secret\"")),
+                    "n": format!("{:016x}", fnv1a("123456")),
+                    "b": format!("{:016x}", fnv1a("true")),
+                    "short": format!("{:016x}", fnv1a("\"ab\"")),
+                }),
+            ),
+        ] {
+            let mut v = value.clone();
+            redactor(&[("$.*", redaction.clone())]).redact(&mut v);
+            assert_eq!(v, exp, "{redaction:?}");
+        }
+    }
+
+    #[test]
+    fn remove_array_elements() {
+        let mut value = json!([0, 1, 2, 3, 4, 5]);
+        let report = redactor(&[
+            ("$[?@ == 0 || @ == 2 || @ == 4]", Redaction::Remove),
+            ("$[1]", Redaction::Remove),
+        ])
+        .redact_with_report(&mut value);
+        assert_eq!(value, json!([3, 5]));
+        assert_eq!(report.len(), 4);
+    }
+
+    #[test]
+    fn overlapping_matches() {
+        let mut value = json!({"a": {"b": "c"}, "d": "e"});
+        let report = redactor(&[
+            ("$.d", Redaction::Replace(json!("first"))),
+            ("$..b", Redaction::Remove),
+            ("$.a", Redaction::Replace(json!("ancestor"))),
+            ("$.*", Redaction::Replace(json!("last"))),
+        ])
+        .redact_with_report(&mut value);
+        assert_eq!(value, json!({"a": "ancestor", "d": "first"}));
+        let locations: Vec<String> = report.iter().map(ToString::to_string).collect();
+        assert_eq!(locations, ["$['a']", "$['d']"]);
+    }
+
+    #[test]
+    fn redact_root() {
+        let mut value = json!({"a": 1});
+        redactor(&[("$", Redaction::Remove)]).redact(&mut value);
+        assert_eq!(value, json!(null));
+    }
+}
@@ -0,0 +1,386 @@
+//! Extraction of tabular data from JSON values using JSONPath queries
+use serde_json::Value;
+use serde_json_path_core::{
+    context::EvalContext,
+    node::LocatedNode,
+    spec::{functions::JsonPathValue, query::Queryable},
+};
+
+use crate::{
+    analysis::{function_uses_locations, uses_locations},
+    parser::{parse_column_main, ParsedColumn},
+    JsonPath, ParseError, TableError,
+};
+
+/// How the nodes matched by a column of a [`TableExtractor`] are combined into a single cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cardinality {
+    /// Expect at most one matched node, which is used as the cell
+    ///
+    /// If there are no matched nodes, the cell is `null`, and if there is more than one, an error
+    /// is produced.
+    #[default]
+    AtMostOne,
+    /// Collect all of the matched nodes into an array, which is used as the cell
+    All,
+}
+
+impl ParsedColumn {
+    /// Check if evaluating the column needs the location of the row
+    fn uses_locations(&self) -> bool {
+        match self {
+            ParsedColumn::Query(query) => uses_locations(query),
+            ParsedColumn::Function(func) => function_uses_locations(func),
+        }
+    }
+
+    fn evaluate<'b>(
+        &'b self,
+        row: &LocatedNode<'b>,
+        root: &'b Value,
+        locations: bool,
+    ) -> Vec<Value> {
+        // the location of the row is made available to functions, and to parent segments:
+        let ctx = if locations {
+            EvalContext::new(root).track_locations_at(row.location().clone())
+        } else {
+            EvalContext::new(root)
+        };
+        match self {
+            ParsedColumn::Query(query) if locations => query
+                .query_located_in(row.node(), &ctx, row.location().clone())
+                .into_iter()
+                .map(|n| n.node().clone())
+                .collect(),
+            ParsedColumn::Query(query) => query
+                .query_in(row.node(), &ctx)
+                .into_iter()
+                .cloned()
+                .collect(),
+            ParsedColumn::Function(func) => match func.evaluate_in(row.node(), &ctx) {
+                JsonPathValue::Nodes(nodes) => nodes.into_iter().cloned().collect(),
+                JsonPathValue::Logical(l) => vec![Value::Bool(l.into())],
+                JsonPathValue::Node(node) => vec![node.clone()],
+                JsonPathValue::Value(value) => vec![value],
+                JsonPathValue::Nothing => vec![],
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    name: String,
+    expr: ParsedColumn,
+    cardinality: Cardinality,
+    /// Whether evaluating the column needs the location of the row
+    locations: bool,
+}
+
+/// Extracts a table, i.e., rows of cells under named columns, from a [`serde_json::Value`]
+///
+/// A `TableExtractor` is configured with a row query, which selects the nodes that produce each
+/// row, and a set of named column expressions that are evaluated against each row to produce its
+/// cells. A column expression is one of:
+///
+/// * a relative query, e.g., `@.customer.name`, which is evaluated with the row as the current
+///   node;
+/// * an absolute query, e.g., `$.meta.date`, which is evaluated against the root node; or
+/// * a function expression, e.g., `count(@.items[*])`, which is evaluated as it would be in a
+///   filter, with the row as the current node.
+///
+/// The nodes produced by a column expression are combined into a single cell according to the
+/// column's [`Cardinality`]. The table can be produced as rows of [`serde_json::Value`]s using
+/// [`extract`][TableExtractor::extract], or as CSV text using [`to_csv`][TableExtractor::to_csv].
+///
+/// # Example
+/// ```rust
+/// # use serde_json::json;
+/// # use serde_json_path::{Cardinality, JsonPath, TableExtractor};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let value = json!({
+///     "orders": [
+///         {"id": 1, "customer": {"name": "Alice"}, "items": [{"sku": "a"}, {"sku": "b"}]},
+///         {"id": 2, "customer": {"name": "Bob, Jr."}, "items": []}
+///     ]
+/// });
+/// let table = TableExtractor::new(JsonPath::parse("$.orders[*]")?)
+///     .column("id", "@.id")?
+///     .column("customer", "@.customer.name")?
+///     .column("items", "count(@.items[*])")?
+///     .column_with("skus", "@.items[*].sku", Cardinality::All)?;
+/// assert_eq!(table.extract(&value)?, [
+///     [json!(1), json!("Alice"), json!(2), json!(["a", "b"])],
+///     [json!(2), json!("Bob, Jr."), json!(0), json!([])],
+/// ]);
+/// assert_eq!(
+///     table.to_csv(&value)?,
+///     "id,customer,items,skus\r\n\
+///      1,Alice,2,\"[\"\"a\"\",\"\"b\"\"]\"\r\n\
+///      2,\"Bob, Jr.\",0,[]\r\n",
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TableExtractor {
+    rows: JsonPath,
+    columns: Vec<Column>,
+}
+
+impl TableExtractor {
+    /// Create a new [`TableExtractor`] whose rows are produced by the nodes matched by `rows`
+    pub fn new(rows: JsonPath) -> Self {
+        Self {
+            rows,
+            columns: Vec::new(),
+        }
+    }
+
+    /// Add a column using [`Cardinality::AtMostOne`]
+    ///
+    /// # Errors
+    ///
+    /// This will error if `expr` is not a valid column expression.
+    pub fn column(self, name: impl Into<String>, expr: &str) -> Result<Self, ParseError> {
+        self.column_with(name, expr, Cardinality::AtMostOne)
+    }
+
+    /// Add a column using the given [`Cardinality`]
+    ///
+    /// # Errors
+    ///
+    /// This will error if `expr` is not a valid column expression.
+    pub fn column_with(
+        mut self,
+        name: impl Into<String>,
+        expr: &str,
+        cardinality: Cardinality,
+    ) -> Result<Self, ParseError> {
+        let (_, expr) = parse_column_main(expr).map_err(|err| match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => (expr, e),
+            nom::Err::Incomplete(_) => unreachable!("we do not use streaming parsers"),
+        })?;
+        self.columns.push(Column {
+            name: name.into(),
+            locations: expr.uses_locations(),
+            expr,
+            cardinality,
+        });
+        Ok(self)
+    }
+
+    /// Get the names of the columns, in the order in which they were added
+    pub fn headers(&self) -> Vec<&str> {
+        self.columns.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    /// Extract the rows of the table from `value`
+    ///
+    /// Rows are produced in the order in which the row query produces its nodes, and the cells in
+    /// each row are in the same order as the [`headers`][TableExtractor::headers].
+    ///
+    /// # Errors
+    ///
+    /// This will error if a column using [`Cardinality::AtMostOne`] matches more than one node.
+    pub fn extract(&self, value: &Value) -> Result<Vec<Vec<Value>>, TableError> {
+        self.rows
+            .query_located(value)
+            .into_iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .map(|column| {
+                        let mut nodes = column.expr.evaluate(&row, value, column.locations);
+                        match column.cardinality {
+                            Cardinality::All => Ok(Value::Array(nodes)),
+                            Cardinality::AtMostOne if nodes.len() > 1 => Err(TableError::new(
+                                row.location().clone().into_owned(),
+                                column.name.clone(),
+                                nodes.len(),
+                            )),
+                            Cardinality::AtMostOne => Ok(nodes.pop().unwrap_or(Value::Null)),
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Extract the table from `value` as [RFC 4180][rfc] CSV text, with a header row
+    ///
+    /// Strings are written as their contents, `null` as an empty field, and other values as
+    /// their JSON representation. Fields are quoted when necessary, and each row, including the
+    /// last, is terminated by a CRLF line break.
+    ///
+    /// # Errors
+    ///
+    /// This will error if a column using [`Cardinality::AtMostOne`] matches more than one node.
+    ///
+    /// [rfc]: https://datatracker.ietf.org/doc/html/rfc4180
+    pub fn to_csv(&self, value: &Value) -> Result<String, TableError> {
+        let rows = self.extract(value)?;
+        let mut csv = String::new();
+        write_csv_record(&mut csv, self.headers());
+        for row in &rows {
+            write_csv_record(
+                &mut csv,
+                row.iter().map(|cell| match cell {
+                    Value::Null => String::new(),
+                    Value::String(s) => s.to_owned(),
+                    other => other.to_string(),
+                }),
+            );
+        }
+        Ok(csv)
+    }
+}
+
+fn write_csv_record<I, S>(csv: &mut String, fields: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            csv.push(',');
+        }
+        let field = field.as_ref();
+        if field.contains([',', '"', '\r', '\n']) {
+            csv.push('"');
+            csv.push_str(&field.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(field);
+        }
+    }
+    csv.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Cardinality, TableExtractor};
+    use crate::JsonPath;
+
+    fn table(rows: &str) -> TableExtractor {
+        TableExtractor::new(JsonPath::parse(rows).expect("valid query"))
+    }
+
+    #[test]
+    fn column_expressions() {
+        let value = json!({"date": "2024-01-01", "rows": [{"a": 1, "b": [1, 2, 3]}, {"a": "x"}]});
+        let t = table("$.rows[*]")
+            .column("a", "@.a")
+            .and_then(|t| t.column("b_len", "length(@.b)"))
+            .and_then(|t| t.column("has_b", "match(@.a, 'x')"))
+            .and_then(|t| t.column("date", "$.date"))
+            .and_then(|t| t.column("self", "@"))
+            .unwrap();
+        assert_eq!(t.headers(), ["a", "b_len", "has_b", "date", "self"]);
+        assert_eq!(
+            t.extract(&value).unwrap(),
+            [
+                vec![
+                    json!(1),
+                    json!(3),
+                    json!(false),
+                    json!("2024-01-01"),
+                    json!({"a": 1, "b": [1, 2, 3]})
+                ],
+                vec![
+                    json!("x"),
+                    json!(null),
+                    json!(true),
+                    json!("2024-01-01"),
+                    json!({"a": "x"})
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_column_expressions() {
+        for expr in [
+            "id",
+            "@.",
+            "nope(@.a)",
+            "count(@.a",
+            "@.a == 1",
+            "length(@.*)",
+        ] {
+            assert!(table("$").column("c", expr).is_err(), "{expr}");
+        }
+    }
+
+    #[test]
+    fn cardinality() {
+        let value = json!([{"t": []}, {"t": ["x"]}, {"t": ["x", "y"]}]);
+        let all = table("$[*]")
+            .column_with("t", "@.t[*]", Cardinality::All)
+            .unwrap();
+        assert_eq!(
+            all.extract(&value).unwrap(),
+            [[json!([])], [json!(["x"])], [json!(["x", "y"])]]
+        );
+        let one = table("$[*]").column("t", "@.t[*]").unwrap();
+        let err = one.extract(&value).unwrap_err();
+        assert_eq!(err.row().to_string(), "$[2]");
+        assert_eq!(err.column(), "t");
+        assert_eq!(err.count(), 2);
+        assert_eq!(
+            one.extract(&json!([{"t": []}, {"t": ["x"]}])).unwrap(),
+            [[json!(null)], [json!("x")]]
+        );
+    }
+
+    #[test]
+    fn csv_escaping() {
+        let value = json!([{"v": "plain"}, {"v": "a,b"}, {"v": "say \"hi\""}, {"v": "two\nlines"}, {"v": null}, {"v": 1.5}]);
+        let csv = table("$[*]")
+            .column("v,alue", "@.v")
+            .unwrap()
+            .to_csv(&value)
+            .unwrap();
+        assert_eq!(
+            csv,
+            "\"v,alue\"\r\nplain\r\n\"a,b\"\r\n\"say \"\"hi\"\"\"\r\n\"two\nlines\"\r\n\r\n1.5\r\n"
+        );
+    }
+
+    #[cfg(feature = "location-functions")]
+    #[test]
+    fn location_columns() {
+        let value = json!({"orders": {"a": {"items": [1, 2]}, "b": {"items": [3]}}});
+        let t = table("$.orders.*")
+            .column("key", "key()")
+            .and_then(|t| t.column("first", "@.items[?index() == 0]"))
+            .unwrap();
+        assert_eq!(
+            t.extract(&value).unwrap(),
+            [[json!("a"), json!(1)], [json!("b"), json!(3)]]
+        );
+    }
+
+    #[cfg(feature = "parent-selector")]
+    #[test]
+    fn parent_columns() {
+        let value = json!({"orders": [
+            {"id": 1, "lines": [{"sku": "x"}, {"sku": "y"}]},
+            {"id": 2, "lines": [{"sku": "z"}]},
+        ]});
+        let t = table("$.orders[*].lines[*]")
+            .column("order", "@^^.id")
+            .and_then(|t| t.column("sku", "@.sku"))
+            .unwrap();
+        assert_eq!(
+            t.extract(&value).unwrap(),
+            [
+                [json!(1), json!("x")],
+                [json!(1), json!("y")],
+                [json!(2), json!("z")]
+            ]
+        );
+    }
+}
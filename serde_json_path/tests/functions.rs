@@ -1,8 +1,8 @@
 use std::cmp::Ordering;
 
 use serde_json::{json, Value};
-use serde_json_path::{JsonPath, JsonPathExt};
-use serde_json_path_core::spec::functions::{NodesType, ValueType};
+use serde_json_path::{Context, JsonPath, JsonPathExt};
+use serde_json_path_core::spec::functions::{FunctionContext, LogicalType, NodesType, ValueType};
 #[cfg(feature = "trace")]
 use test_log::test;
 
@@ -226,3 +226,542 @@ fn combine_custom_functions() {
         node.pointer("/books/1/title").unwrap().as_str().unwrap(),
     );
 }
+
+struct MaxDepth(usize);
+
+/// Check that the current node is no deeper than the `MaxDepth` supplied in the context
+#[serde_json_path::function]
+fn shallow(ctx: &FunctionContext) -> LogicalType {
+    match (ctx.location(), ctx.data::<MaxDepth>()) {
+        (Some(loc), Some(MaxDepth(max))) => (loc.depth() <= *max).into(),
+        _ => LogicalType::False,
+    }
+}
+
+/// Get the value at `key` in the root node, with the function context between the arguments
+#[serde_json_path::function]
+fn lookup<'a>(
+    node: ValueType<'a>,
+    ctx: &FunctionContext<'_, 'a>,
+    other: ValueType<'a>,
+) -> ValueType<'a> {
+    let (Some(key), Some(other)) = (node.as_value().and_then(Value::as_str), other.as_value())
+    else {
+        return ValueType::Nothing;
+    };
+    match ctx.root().get("table").and_then(|t| t.get(key)) {
+        Some(v) if v == other => ValueType::Node(v),
+        _ => ValueType::Nothing,
+    }
+}
+
+#[test]
+fn function_context() {
+    let value = json!({"a": [{"b": [1]}, {"b": []}], "c": {"b": [2]}});
+    let path = JsonPath::parse("$..[?shallow() && @.b]").unwrap();
+    let context = Context::new().with_data(MaxDepth(2));
+    let nodes = path.query_with_context(&value, &context);
+    assert_eq!(
+        nodes.all(),
+        vec![&json!({"b": [2]}), &json!({"b": [1]}), &json!({"b": []})]
+    );
+    // the context can be dropped before the nodes:
+    let nodes = path.query_with_context(&value, &Context::new().with_data(MaxDepth(1)));
+    assert_eq!(nodes.all(), vec![&json!({"b": [2]})]);
+    // without the data, the function has nothing to go on:
+    assert!(path.query(&value).is_empty());
+    assert!(path.query_with_context(&value, &Context::new()).is_empty());
+}
+
+/// Get the depth of the current node
+#[serde_json_path::function]
+fn depth(ctx: &FunctionContext) -> Option<i64> {
+    ctx.location()
+        .and_then(|loc| i64::try_from(loc.depth()).ok())
+}
+
+#[test]
+fn function_context_location_in_query() {
+    let value = json!({"a": [{"b": 1}, 2], "c": {"d": 3}});
+    // locations are tracked by any query that calls a function accepting a function context:
+    let path = JsonPath::parse("$..[?depth() == 2]").unwrap();
+    assert_eq!(
+        path.query(&value).all(),
+        vec![&json!({"b": 1}), &json!(2), &json!(3)]
+    );
+    assert_eq!(path.query_located(&value).len(), 3);
+}
+
+#[test]
+fn function_context_in_nested_filters() {
+    let value = json!([{"xs": [1, {"y": 1}]}, {"xs": [{"y": 2}]}]);
+    // the nested filter tests nodes at a depth of 3:
+    let path = JsonPath::parse("$[?@.xs[?shallow() && @.y]]").unwrap();
+    let context = Context::new().with_data(MaxDepth(3));
+    assert_eq!(path.query_with_context(&value, &context).len(), 2);
+    let context = Context::new().with_data(MaxDepth(2));
+    assert!(path.query_with_context(&value, &context).is_empty());
+}
+
+#[test]
+fn function_context_is_not_an_argument() {
+    let value = json!({"table": {"a": 1, "b": 2}, "items": [["a", 1], ["b", 1], ["c", 1]]});
+    let path = JsonPath::parse("$.items[?lookup(@[0], @[1]) == @[1]]").unwrap();
+    let nodes = path.query(&value);
+    assert_eq!(nodes.all(), vec![&json!(["a", 1])]);
+    assert!(JsonPath::parse("$[?lookup(@.a)]").is_err());
+    assert!(JsonPath::parse("$[?shallow(@.a)]").is_err());
+}
+
+/// Check that a string is a valid semantic version, or fail if it cannot be parsed
+#[serde_json_path::function]
+fn is_major(version: ValueType, major: ValueType) -> Result<LogicalType, String> {
+    let (Some(version), Some(major)) = (
+        version.as_value().and_then(Value::as_str),
+        major.as_value().and_then(Value::as_u64),
+    ) else {
+        return Ok(LogicalType::False);
+    };
+    match version.split('.').next().map(str::parse::<u64>) {
+        Some(Ok(m)) => Ok((m == major).into()),
+        _ => Err(format!("invalid version '{version}'")),
+    }
+}
+
+#[test]
+fn fallible_function() {
+    let value = json!({
+        "a": {"deps": [{"v": "1.0.0"}, {"v": "2.1.0"}]},
+        "b": {"deps": [{"v": "x.y"}, {"v": "1.2.3"}, {"v": "?"}]},
+    });
+    let path = JsonPath::parse("$.*.deps[?is_major(@.v, 1)]").unwrap();
+    // lenient evaluation treats the failures as false:
+    assert_eq!(
+        path.query(&value).all(),
+        vec![&json!({"v": "1.0.0"}), &json!({"v": "1.2.3"})]
+    );
+    // strict evaluation produces the first failure:
+    let err = path.try_query(&value).unwrap_err();
+    assert_eq!(err.function(), "is_major");
+    assert_eq!(err.location().to_string(), "$['b']['deps'][0]");
+    assert_eq!(err.error().to_string(), "invalid version 'x.y'");
+    // queries without failures succeed:
+    let path = JsonPath::parse("$.a.deps[?is_major(@.v, 2)]").unwrap();
+    assert_eq!(
+        path.try_query(&value).unwrap().all(),
+        vec![&json!({"v": "2.1.0"})]
+    );
+}
+
+#[test]
+fn fallible_function_in_nested_filter() {
+    let value = json!([{"deps": [{"v": "1.0"}]}, {"deps": [{"v": "one"}]}]);
+    let path = JsonPath::parse("$[?@.deps[?is_major(@.v, 1)]]").unwrap();
+    assert_eq!(path.query(&value).len(), 1);
+    let err = path.try_query(&value).unwrap_err();
+    assert_eq!(err.location().to_string(), "$[1]['deps'][0]");
+}
+
+#[serde_json_path::function]
+fn discounted(price: f64, percent: Option<i64>) -> Option<f64> {
+    let discounted = price * (100 - percent.unwrap_or(0)) as f64 / 100.0;
+    (discounted >= 0.0).then_some(discounted)
+}
+
+#[serde_json_path::function]
+fn has_prefix(s: &str, prefix: &str) -> bool {
+    s.starts_with(prefix)
+}
+
+#[serde_json_path::function]
+fn describe(value: &Value, verbose: bool) -> String {
+    if verbose {
+        format!("value: {value}")
+    } else {
+        value.to_string()
+    }
+}
+
+#[test]
+fn rust_typed_arguments() {
+    let value = json!([
+        {"name": "apple", "price": 10, "off": 50},
+        {"name": "avocado", "price": 3.0},
+        {"name": "banana", "price": "free", "off": 10},
+        {"name": "almond", "price": 8, "off": 1.5},
+        {"name": 42, "price": 1},
+    ]);
+    let path = JsonPath::parse("$[?discounted(@.price, @.off) <= 5]").unwrap();
+    // `off` is not an integer in the last item, so it is taken as `None`, while the item with a
+    // string price produces nothing:
+    assert_eq!(
+        path.query(&value).all(),
+        vec![&value[0], &value[1], &value[4]]
+    );
+    let path = JsonPath::parse("$[?has_prefix(@.name, 'a')]").unwrap();
+    assert_eq!(path.query(&value).len(), 3);
+    let path = JsonPath::parse("$[?describe(@.name, @.off) == 'value: \"banana\"']").unwrap();
+    assert_eq!(path.query(&value).all(), vec![&value[2]]);
+    let path = JsonPath::parse("$[?describe(@.name, @.verbose) == '42']").unwrap();
+    assert_eq!(path.query(&value).all(), vec![&value[4]]);
+}
+
+#[test]
+fn rust_typed_arguments_are_validated() {
+    // `bool` parameters are logical, and other parameters are values:
+    for query in [
+        "$[?has_prefix(@.a)]",
+        "$[?has_prefix(@.*, 'a')]",
+        "$[?discounted(@.a, @.b)]",
+        "$[?describe(@.a, 'verbose') == 'a']",
+    ] {
+        assert!(JsonPath::parse(query).is_err(), "{query}");
+    }
+    assert!(JsonPath::parse("$[?describe(@.a, @.*) == 'a']").is_ok());
+}
+
+/// Produce the first of its arguments that is not nothing
+#[serde_json_path::function]
+fn first_of<'a>(#[variadic] values: Vec<ValueType<'a>>) -> ValueType<'a> {
+    values
+        .into_iter()
+        .find(|v| !v.is_nothing())
+        .unwrap_or_default()
+}
+
+#[serde_json_path::function]
+fn join_all(separator: &str, #[variadic] parts: Vec<&str>) -> String {
+    parts.join(separator)
+}
+
+#[serde_json_path::function]
+fn round_to(x: f64, #[optional] digits: Option<i64>) -> Option<f64> {
+    let factor = 10f64.powi(i32::try_from(digits.unwrap_or(0)).ok()?);
+    Some((x * factor).round() / factor)
+}
+
+#[test]
+fn variadic_and_optional_arguments() {
+    let value = json!([
+        {"nick": "al", "name": "Alice", "score": 1.26},
+        {"name": "Bob", "first": "B", "last": "ob", "score": 2.5},
+        {"first": "C", "last": 1, "score": "n/a"},
+    ]);
+    for (query, expected) in [
+        ("$[?first_of(@.nick, @.name) == 'al']", vec![0]),
+        ("$[?first_of(@.nick, @.name) == 'Bob']", vec![1]),
+        ("$[?first_of(@.nick) == 'Bob']", vec![]),
+        ("$[?first_of() == 'Bob']", vec![]),
+        ("$[?first_of(@.nick, @.name, @.first) == 'C']", vec![2]),
+        ("$[?join_all('', @.first, @.last) == 'Bob']", vec![1]),
+        // any argument that is not a string produces nothing:
+        ("$[?join_all('', @.first, @.last) == 'C1']", vec![]),
+        ("$[?join_all('-') == '']", vec![0, 1, 2]),
+        ("$[?round_to(@.score) == 3]", vec![1]),
+        ("$[?round_to(@.score, 1) == 1.3]", vec![0]),
+        // an optional argument that is given, but can not be converted, produces nothing:
+        ("$[?round_to(@.score, @.digits) == 1]", vec![]),
+    ] {
+        let path = JsonPath::parse(query).unwrap();
+        let nodes: Vec<&Value> = expected.into_iter().map(|i| &value[i]).collect();
+        assert_eq!(path.query(&value).all(), nodes, "{query}");
+    }
+}
+
+#[test]
+fn variadic_and_optional_arguments_are_validated() {
+    for (query, message) in [
+        (
+            "$[?join_all() == 1]",
+            "in function join_all, expected at least 1 args, but received 0",
+        ),
+        (
+            "$[?round_to(@.a, 1, 2) == 1]",
+            "in function round_to, expected at most 2 args, but received 3",
+        ),
+        (
+            "$[?round_to(@.a, @.*) == 1]",
+            "in function round_to, in argument position 1, expected a type that converts to \
+                ValueType, received nodes type",
+        ),
+        (
+            "$[?join_all('', @.a, @.b, @.*) == 1]",
+            "in function join_all, in argument position 3, expected a type that converts to \
+                ValueType, received nodes type",
+        ),
+        (
+            "$[?length(@.a, @.b) == 1]",
+            "expected 1 args, but received 2",
+        ),
+    ] {
+        let err = JsonPath::parse(query).unwrap_err();
+        assert!(
+            err.to_string().contains(message),
+            "{query}: {err} does not contain {message}"
+        );
+    }
+}
+
+#[cfg(feature = "string-functions")]
+#[test]
+fn string_functions() {
+    let value = json!([
+        {"name": "Admin", "email": " admin@example.com "},
+        {"name": "adminton", "email": "a@b.c"},
+        {"name": "Bob", "email": "bob@example.org"},
+        {"name": 1},
+    ]);
+    for (query, expected) in [
+        ("$[?starts_with(lower(@.name), 'adm')]", vec![0, 1]),
+        ("$[?starts_with(@.name, 'adm')]", vec![1]),
+        ("$[?ends_with(trim(@.email), '.com')]", vec![0]),
+        ("$[?ends_with(@.email, '.com')]", vec![]),
+        ("$[?contains(@.email, 'example')]", vec![0, 2]),
+        ("$[?upper(@.name) == 'BOB']", vec![2]),
+        ("$[?substring(@.name, 0, 3) == 'Adm']", vec![0]),
+        ("$[?substring(@.name, -3) == 'ton']", vec![1]),
+        ("$[?substring(@.name, 1, 100) == 'ob']", vec![2]),
+        ("$[?split_count(@.email, '@') == 2]", vec![0, 1, 2]),
+        ("$[?split_count(@.email, '') == 1]", vec![]),
+        (
+            "$[?concat(@.name, '<', trim(@.email), '>') == 'Bob<bob@example.org>']",
+            vec![2],
+        ),
+        ("$[?length(concat(@.name, @.name)) == 6]", vec![2]),
+        // arguments that are not strings produce nothing, or false:
+        ("$[?lower(@.name) == 1]", vec![]),
+        ("$[?contains(@.name, 1)]", vec![]),
+    ] {
+        let path = JsonPath::parse(query).unwrap();
+        let nodes: Vec<&Value> = expected.into_iter().map(|i| &value[i]).collect();
+        assert_eq!(path.query(&value).all(), nodes, "{query}");
+    }
+    for query in [
+        "$[?lower(@.name)]",
+        "$[?starts_with(@.name) == true]",
+        "$[?starts_with(lower(@.*), 'a')]",
+        "$[?substring(@.name, 1, 2, 3) == 'a']",
+    ] {
+        assert!(JsonPath::parse(query).is_err(), "{query}");
+    }
+}
+
+#[cfg(feature = "numeric-functions")]
+#[test]
+fn numeric_functions() {
+    let value = json!({
+        "invoices": [
+            {"lines": [{"amount": 600}, {"amount": 500.5}]},
+            {"lines": [{"amount": 100}, {"amount": -3}]},
+            {"lines": [{"amount": 2000}, {"amount": "free"}]},
+            {"lines": []},
+            {"lines": [{"amount": 9007199254740993_u64}, {"amount": 9007199254740992_u64}]},
+        ]
+    });
+    let invoices = value["invoices"].as_array().unwrap();
+    for (query, expected) in [
+        ("$.invoices[?sum(@.lines[*].amount) > 1000]", vec![0, 4]),
+        ("$.invoices[?sum(@.lines[*].amount) == 97]", vec![1]),
+        ("$.invoices[?sum(@.lines[*].amount) == 0]", vec![3]),
+        (
+            "$.invoices[?sum(@.lines[*].amount) == 18014398509481985]",
+            vec![4],
+        ),
+        ("$.invoices[?min(@.lines[*].amount) < 0]", vec![1]),
+        ("$.invoices[?max(@.lines[*].amount) == 600]", vec![0]),
+        (
+            "$.invoices[?max(@.lines[*].amount) == 9007199254740993]",
+            vec![4],
+        ),
+        ("$.invoices[?avg(@.lines[*].amount) == 48.5]", vec![1]),
+        ("$.invoices[?avg(@.lines[*].amount) >= 0]", vec![0, 1, 4]),
+        ("$.invoices[?abs(@.lines[1].amount) == 3]", vec![1]),
+        ("$.invoices[?floor(@.lines[1].amount) == 500]", vec![0]),
+        ("$.invoices[?ceil(@.lines[1].amount) == 501]", vec![0]),
+        ("$.invoices[?round(@.lines[1].amount) == 501]", vec![0]),
+        ("$.invoices[?round(@.lines[1].amount, 1) == 500.5]", vec![0]),
+        ("$.invoices[?round(@.lines[0].amount, -2) == 2000]", vec![2]),
+        (
+            "$.invoices[?round(@.lines[1].amount, 400) == 500.5]",
+            vec![0],
+        ),
+        (
+            "$.invoices[?round(@.lines[1].amount, -400) == 0]",
+            vec![0, 1, 4],
+        ),
+        // values that are not numbers produce nothing:
+        ("$.invoices[?abs(@.lines[1].amount) == 'free']", vec![]),
+        (
+            "$.invoices[?floor(@.lines[1].amount) == floor(@.nope)]",
+            vec![2, 3],
+        ),
+    ] {
+        let path = JsonPath::parse(query).unwrap();
+        let nodes: Vec<&Value> = expected.into_iter().map(|i| &invoices[i]).collect();
+        assert_eq!(path.query(&value).all(), nodes, "{query}");
+    }
+    for query in [
+        "$.invoices[?sum(@.lines[0].amount)]",
+        "$.invoices[?sum(@.lines[*].amount, 1) > 0]",
+        "$.invoices[?abs(@.lines[*].amount) > 0]",
+    ] {
+        assert!(JsonPath::parse(query).is_err(), "{query}");
+    }
+}
+
+#[cfg(feature = "type-functions")]
+#[test]
+fn type_functions() {
+    let value = json!({
+        "members": [
+            {"value": "a"},
+            {"value": 1.5},
+            {"value": true},
+            {"value": null},
+            {"value": {"a": 1}},
+            {"value": [1, 2]},
+            {},
+        ]
+    });
+    let members = value["members"].as_array().unwrap();
+    for (query, expected) in [
+        ("$.members[?type(@.value) == 'string']", vec![0]),
+        ("$.members[?type(@.value) == 'number']", vec![1]),
+        ("$.members[?type(@.value) == 'boolean']", vec![2]),
+        ("$.members[?type(@.value) == 'null']", vec![3]),
+        ("$.members[?type(@.value) == 'object']", vec![4]),
+        ("$.members[?type(@.value) == 'array']", vec![5]),
+        ("$.members[?type(@.value) == type(@.nope)]", vec![6]),
+        ("$.members[?is_string(@.value)]", vec![0]),
+        ("$.members[?is_number(@.value)]", vec![1]),
+        ("$.members[?is_boolean(@.value)]", vec![2]),
+        ("$.members[?is_null(@.value)]", vec![3]),
+        ("$.members[?is_object(@.value)]", vec![4]),
+        ("$.members[?is_array(@.value)]", vec![5]),
+        ("$.members[?!is_null(@.value)]", vec![0, 1, 2, 4, 5, 6]),
+        ("$.members[?is_number(@.value.a)]", vec![4]),
+    ] {
+        let path = JsonPath::parse(query).unwrap();
+        let nodes: Vec<&Value> = expected.into_iter().map(|i| &members[i]).collect();
+        assert_eq!(path.query(&value).all(), nodes, "{query}");
+    }
+    for query in [
+        "$.members[?type(@.value)]",
+        "$.members[?is_array(@.value) == true]",
+        "$.members[?is_array(@.*)]",
+    ] {
+        assert!(JsonPath::parse(query).is_err(), "{query}");
+    }
+}
+
+#[cfg(feature = "datetime")]
+#[test]
+fn datetime_functions() {
+    let value = json!({
+        "events": [
+            {"ts": "2023-12-31T23:59:59Z"},
+            {"ts": "2024-01-01T01:30:00+02:00"},
+            {"ts": "2024-01-01T00:00:00.5Z"},
+            {"ts": "2024-01-08T00:00:00z"},
+            {"ts": "2024-01-01"},
+            {"ts": 1704067200},
+        ]
+    });
+    let events = value["events"].as_array().unwrap();
+    for (query, expected) in [
+        (
+            "$.events[?date_before('2024-01-01T00:00:00Z', @.ts)]",
+            vec![2, 3],
+        ),
+        (
+            "$.events[?date_before(@.ts, '2024-01-01T00:00:00Z')]",
+            vec![0, 1],
+        ),
+        ("$.events[?timestamp(@.ts) == 1704067199]", vec![0]),
+        ("$.events[?timestamp(@.ts) == 1704065400]", vec![1]),
+        ("$.events[?timestamp(@.ts) == 1704067200.5]", vec![2]),
+        (
+            "$.events[?timestamp(@.ts) > timestamp('2024-01-01T00:00:00Z')]",
+            vec![2, 3],
+        ),
+        (
+            "$.events[?date_diff(@.ts, '2024-01-01T00:00:00Z', 'weeks') == 1]",
+            vec![3],
+        ),
+        (
+            "$.events[?date_diff(@.ts, '2024-01-01T00:00:00Z', 'minutes') == -30]",
+            vec![1],
+        ),
+        (
+            "$.events[?date_diff(@.ts, '2024-01-01T00:00:00Z', 'milliseconds') == 500]",
+            vec![2],
+        ),
+        (
+            "$.events[?date_diff(@.ts, '2024-01-01T00:00:00Z', 'seconds') == 0.5]",
+            vec![2],
+        ),
+        // invalid date-times and units produce nothing, or false:
+        (
+            "$.events[?timestamp(@.ts) == timestamp(@.nope)]",
+            vec![4, 5],
+        ),
+        (
+            "$.events[?date_diff(@.ts, @.ts, 'months') == date_diff(@.ts, @.ts, 'years')]",
+            vec![0, 1, 2, 3, 4, 5],
+        ),
+        ("$.events[?date_before(@.ts, '2025-01-01')]", vec![]),
+    ] {
+        let path = JsonPath::parse(query).unwrap();
+        let nodes: Vec<&Value> = expected.into_iter().map(|i| &events[i]).collect();
+        assert_eq!(path.query(&value).all(), nodes, "{query}");
+    }
+    for query in [
+        "$.events[?timestamp(@.ts)]",
+        "$.events[?date_before(@.ts)]",
+        "$.events[?date_diff(@.ts, @.ts) == 0]",
+    ] {
+        assert!(JsonPath::parse(query).is_err(), "{query}");
+    }
+}
+
+#[cfg(feature = "location-functions")]
+#[test]
+fn location_functions() {
+    let value = json!({
+        "users": {"admin_alice": {"age": 30}, "bob": {"age": 40}, "admin_carol": {"age": 50}},
+        "items": ["a", "b", "c", "d"],
+    });
+    for (query, expected) in [
+        (
+            "$.users[?search(key(), '^admin_')]",
+            vec![
+                &value["users"]["admin_alice"],
+                &value["users"]["admin_carol"],
+            ],
+        ),
+        (
+            "$.users[?key() == 'bob'].age",
+            vec![&value["users"]["bob"]["age"]],
+        ),
+        ("$.users[?index() == 0]", vec![]),
+        (
+            "$.items[?index() < 2]",
+            vec![&value["items"][0], &value["items"][1]],
+        ),
+        ("$.items[?key() == 'a']", vec![]),
+        // the location is that of the node tested by the innermost filter:
+        ("$[?@[?key() == 'bob']]", vec![&value["users"]]),
+        ("$[?@[?index() == 3]]", vec![&value["items"]]),
+    ] {
+        let path = JsonPath::parse(query).unwrap();
+        assert_eq!(path.query(&value).all(), expected, "{query}");
+        assert_eq!(
+            path.query_located(&value).nodes().collect::<Vec<_>>(),
+            expected,
+            "{query}"
+        );
+    }
+    for query in ["$.items[?key()]", "$.items[?index(@) == 0]"] {
+        assert!(JsonPath::parse(query).is_err(), "{query}");
+    }
+}
@@ -0,0 +1,238 @@
+//! Data supplied to a JSONPath query at the time it is evaluated
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    error::Error,
+    rc::Rc,
+    sync::Arc,
+};
+
+use serde_json::Value;
+
+use crate::path::NormalizedPath;
+
+/// Data supplied to a JSONPath query at the time it is evaluated
+///
+/// A [`Context`] binds named variables, which can be referred to within filter expressions using
+/// the non-standard `%name` syntax. A variable can be any JSON value, including an entire
+/// document, and can be followed by name and index segments to refer to a node within it, e.g.,
+/// `%tenant.limits['price']`.
+///
+/// A variable that is not bound in the [`Context`] produces nothing, in the same way that a
+/// singular query that does not match any node does.
+///
+/// A [`Context`] can also hold application data, such as the current time, or a lookup table,
+/// which is made available to functions through their [`FunctionContext`]. At most one value of
+/// each type can be held.
+///
+/// [`FunctionContext`]: crate::spec::functions::FunctionContext
+#[derive(Default, Clone)]
+pub struct Context {
+    variables: HashMap<String, Value>,
+    data: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl std::fmt::Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Context")
+            .field("variables", &self.variables)
+            .field("data", &format_args!("{n} value(s)", n = self.data.len()))
+            .finish()
+    }
+}
+
+impl Context {
+    /// Create a new, empty, [`Context`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind the variable `name` to `value`
+    pub fn with_var(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.set_var(name, value);
+        self
+    }
+
+    /// Bind the variable `name` to `value`, returning the value it was previously bound to
+    pub fn set_var(&mut self, name: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        self.variables.insert(name.into(), value.into())
+    }
+
+    /// Get the value that the variable `name` is bound to
+    pub fn var(&self, name: &str) -> Option<&Value> {
+        self.variables.get(name)
+    }
+
+    /// Add application data, replacing any existing data of the same type
+    pub fn with_data<T: Any + Send + Sync>(mut self, data: T) -> Self {
+        self.set_data(data);
+        self
+    }
+
+    /// Add application data, replacing any existing data of the same type
+    pub fn set_data<T: Any + Send + Sync>(&mut self, data: T) {
+        self.data.insert(TypeId::of::<T>(), Arc::new(data));
+    }
+
+    /// Get the application data of type `T`, if any
+    pub fn data<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.data
+            .get(&TypeId::of::<T>())
+            .and_then(|d| d.downcast_ref())
+    }
+}
+
+/// Error produced by a function that failed during the evaluation of a query
+///
+/// Functions can fail by returning a `Result`. Such failures are only reported when using a
+/// strict [`EvalContext`]; otherwise, a failed function produces nothing.
+#[derive(Debug, thiserror::Error)]
+#[error("function '{function}' failed when testing the node at {location}: {error}")]
+pub struct EvaluationError {
+    function: String,
+    location: NormalizedPath<'static>,
+    #[source]
+    error: Box<dyn Error + Send + Sync>,
+}
+
+impl EvaluationError {
+    /// Get the name of the function that failed
+    pub fn function(&self) -> &str {
+        &self.function
+    }
+
+    /// Get the location of the node that was being tested by the filter that called the function
+    ///
+    /// If locations were not being tracked, this is the location of the root node.
+    pub fn location(&self) -> &NormalizedPath<'static> {
+        &self.location
+    }
+
+    /// Get the error that the function failed with
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self.error.as_ref()
+    }
+}
+
+/// The environment in which a JSONPath query is evaluated
+///
+/// This holds the root node of the query, along with the optional [`Context`] that was supplied
+/// with it. The [`Context`] has its own lifetime, `'c`, so that it need not outlive the nodes
+/// produced by the query. If location tracking is enabled, it also holds the location of the
+/// node that is being tested by a filter, and if it is strict, it records the first error
+/// produced by a function.
+#[derive(Debug, Clone)]
+pub struct EvalContext<'b, 'c> {
+    root: &'b Value,
+    context: Option<&'c Context>,
+    location: Option<NormalizedPath<'b>>,
+    error: Option<Rc<RefCell<Option<EvaluationError>>>>,
+}
+
+impl<'b, 'c> EvalContext<'b, 'c> {
+    /// Create an [`EvalContext`] for a query against `root`, without a [`Context`]
+    pub fn new(root: &'b Value) -> Self {
+        Self {
+            root,
+            context: None,
+            location: None,
+            error: None,
+        }
+    }
+
+    /// Create an [`EvalContext`] for a query against `root`, with the given [`Context`]
+    pub fn with_context(root: &'b Value, context: &'c Context) -> Self {
+        Self {
+            root,
+            context: Some(context),
+            location: None,
+            error: None,
+        }
+    }
+
+    /// Enable tracking the location of the node being tested by a filter
+    ///
+    /// This makes the location available to functions, through their [`FunctionContext`], at
+    /// the cost of computing the location of every node that is tested.
+    ///
+    /// [`FunctionContext`]: crate::spec::functions::FunctionContext
+    pub fn track_locations(self) -> Self {
+        self.track_locations_at(NormalizedPath::default())
+    }
+
+    /// Enable tracking the location of the node being tested by a filter, where the current node
+    /// of the query is at `location`
+    ///
+    /// This is used to evaluate a relative query or function expression against a node other
+    /// than the root, e.g., one that was produced by another query.
+    pub fn track_locations_at(mut self, location: NormalizedPath<'b>) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Record the first error produced by a function, so that it can be retrieved using
+    /// [`take_error`][EvalContext::take_error]
+    ///
+    /// Functions that fail still produce nothing, so the query runs to completion.
+    pub fn strict(mut self) -> Self {
+        self.error = Some(Default::default());
+        self
+    }
+
+    /// Take the first error produced by a function, if this is strict and one was produced
+    pub fn take_error(&self) -> Option<EvaluationError> {
+        self.error.as_ref().and_then(|e| e.borrow_mut().take())
+    }
+
+    /// Record an error produced by the function `function`, if this is strict and no error has
+    /// been recorded yet
+    pub(crate) fn report_error(&self, function: &str, error: Box<dyn Error + Send + Sync>) {
+        let Some(slot) = &self.error else {
+            return;
+        };
+        let mut slot = slot.borrow_mut();
+        if slot.is_none() {
+            *slot = Some(EvaluationError {
+                function: function.to_owned(),
+                location: self
+                    .location
+                    .clone()
+                    .map(NormalizedPath::into_owned)
+                    .unwrap_or_default(),
+                error,
+            });
+        }
+    }
+
+    /// Get the root node of the query
+    pub fn root(&self) -> &'b Value {
+        self.root
+    }
+
+    /// Get the [`Context`] supplied with the query, if any
+    pub fn context(&self) -> Option<&'c Context> {
+        self.context
+    }
+
+    /// Get the value that the variable `name` is bound to
+    pub fn var(&self, name: &str) -> Option<&'c Value> {
+        self.context.and_then(|c| c.var(name))
+    }
+
+    /// Get the location of the current node, if locations are being tracked
+    pub fn location(&self) -> Option<&NormalizedPath<'b>> {
+        self.location.as_ref()
+    }
+
+    /// Produce an [`EvalContext`] for testing the node at `location`, if locations are being
+    /// tracked
+    pub(crate) fn at(&self, location: impl FnOnce() -> NormalizedPath<'b>) -> Option<Self> {
+        self.location.as_ref().map(|_| Self {
+            root: self.root,
+            context: self.context,
+            location: Some(location()),
+            error: self.error.clone(),
+        })
+    }
+}
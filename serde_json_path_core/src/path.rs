@@ -2,16 +2,22 @@
 //!
 //! [norm-paths]: https://www.rfc-editor.org/rfc/rfc9535.html#name-normalized-paths
 use std::{
+    borrow::Cow,
     cmp::Ordering,
     fmt::Display,
+    iter::Peekable,
     slice::{Iter, SliceIndex},
+    str::{CharIndices, FromStr},
 };
 
-use serde::Serialize;
+use serde::{de::Visitor, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::spec::integer::Integer;
 
 // Documented in the serde_json_path crate, for linking purposes
 #[allow(missing_docs)]
-#[derive(Debug, Default, Eq, PartialEq, Clone, PartialOrd)]
+#[derive(Debug, Default, Eq, PartialEq, Clone, PartialOrd, Hash)]
 pub struct NormalizedPath<'a>(Vec<PathElement<'a>>);
 
 impl<'a> NormalizedPath<'a> {
@@ -73,6 +79,119 @@ impl<'a> NormalizedPath<'a> {
         self.0.len()
     }
 
+    /// Get the depth of the location represented by the [`NormalizedPath`]
+    ///
+    /// This is the number of [`PathElement`]s in the path, such that the root node, `$`, has a
+    /// depth of `0`, its children a depth of `1`, and so on.
+    pub fn depth(&self) -> usize {
+        self.len()
+    }
+
+    /// Get the [`NormalizedPath`] of the parent node, or `None` if this is the root path
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let path: NormalizedPath = "$['foo'][0]".parse()?;
+    /// let parent = path.parent().expect("path is not the root");
+    /// assert_eq!(parent.to_string(), "$['foo']");
+    /// assert_eq!(parent.parent().map(|p| p.to_string()).as_deref(), Some("$"));
+    /// assert!(NormalizedPath::default().parent().is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parent(&self) -> Option<NormalizedPath<'a>> {
+        self.0
+            .split_last()
+            .map(|(_, parent)| NormalizedPath(parent.to_vec()))
+    }
+
+    /// Check if `prefix` is a prefix of this [`NormalizedPath`]
+    ///
+    /// A path is a prefix of another if it locates the same node, or one of its ancestors.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let path: NormalizedPath = "$['foo'][0]".parse()?;
+    /// assert!(path.starts_with(&"$['foo']".parse()?));
+    /// assert!(path.starts_with(&path));
+    /// assert!(!path.starts_with(&"$['bar']".parse()?));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn starts_with(&self, prefix: &NormalizedPath<'_>) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// Get the remainder of this [`NormalizedPath`] after removing `prefix`, or `None` if
+    /// `prefix` is not a prefix of this path
+    ///
+    /// The remainder locates this path's node relative to the node located by `prefix`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let path: NormalizedPath = "$['foo'][0]['bar']".parse()?;
+    /// let rest = path.strip_prefix(&"$['foo']".parse()?).expect("is a prefix");
+    /// assert_eq!(rest.to_string(), "$[0]['bar']");
+    /// assert!(path.strip_prefix(&"$['bar']".parse()?).is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn strip_prefix(&self, prefix: &NormalizedPath<'_>) -> Option<NormalizedPath<'a>> {
+        self.starts_with(prefix)
+            .then(|| NormalizedPath(self.0[prefix.len()..].to_vec()))
+    }
+
+    /// Create a new [`NormalizedPath`] by appending the elements of `other` to this path
+    ///
+    /// This is the inverse of [`strip_prefix`][NormalizedPath::strip_prefix], i.e., it treats
+    /// `other` as relative to the node located by this path.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let base: NormalizedPath = "$['foo']".parse()?;
+    /// let path = base.join(&"$[0]['bar']".parse()?);
+    /// assert_eq!(path.to_string(), "$['foo'][0]['bar']");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn join(&self, other: &NormalizedPath<'a>) -> NormalizedPath<'a> {
+        NormalizedPath(self.0.iter().chain(other.0.iter()).cloned().collect())
+    }
+
+    /// Get the longest [`NormalizedPath`] that is a prefix of both this path and `other`
+    ///
+    /// This locates the closest common ancestor of the two nodes, or the node itself, if both
+    /// paths are the same.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let a: NormalizedPath = "$['foo'][0]['bar']".parse()?;
+    /// let b: NormalizedPath = "$['foo'][0]['baz']".parse()?;
+    /// assert_eq!(a.common_prefix(&b).to_string(), "$['foo'][0]");
+    /// let c: NormalizedPath = "$['bop']".parse()?;
+    /// assert_eq!(a.common_prefix(&c).to_string(), "$");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn common_prefix(&self, other: &NormalizedPath<'_>) -> NormalizedPath<'a> {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.clone())
+            .collect()
+    }
+
     /// Get an iterator over the [`PathElement`]s of the [`NormalizedPath`]
     ///
     /// Note that [`NormalizedPath`] also implements [`IntoIterator`]
@@ -158,6 +277,228 @@ impl<'a> NormalizedPath<'a> {
     pub fn last(&self) -> Option<&PathElement<'a>> {
         self.0.last()
     }
+
+    /// Convert into an owned [`NormalizedPath`] that does not borrow from the queried value
+    ///
+    /// This allows locations to outlive the [`serde_json::Value`] that was queried to produce
+    /// them, e.g., to be stored or sent to another thread.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::{JsonPath, NormalizedPath};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let path = JsonPath::parse("$.foo[*]")?;
+    /// let locations: Vec<NormalizedPath<'static>> = {
+    ///     let value = json!({"foo": ["bar", "baz"]});
+    ///     path.query_located(&value)
+    ///         .locations()
+    ///         .map(|loc| loc.clone().into_owned())
+    ///         .collect()
+    /// };
+    /// assert_eq!(locations[1].to_string(), "$['foo'][1]");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_owned(self) -> NormalizedPath<'static> {
+        NormalizedPath(self.0.into_iter().map(PathElement::into_owned).collect())
+    }
+
+    /// Get a reference to the node at this location in `value`, or `None` if it does not exist
+    ///
+    /// This walks the [`PathElement`]s of the path directly, and is therefore cheaper than
+    /// converting to a JSON Pointer and using [`serde_json::Value::pointer`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let value = json!({"foo": ["bar", "baz"]});
+    /// let path: NormalizedPath = "$['foo'][1]".parse()?;
+    /// assert_eq!(path.resolve(&value), Some(&json!("baz")));
+    /// let path: NormalizedPath = "$['foo'][2]".parse()?;
+    /// assert_eq!(path.resolve(&value), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.try_resolve(value).ok()
+    }
+
+    /// Get a mutable reference to the node at this location in `value`, or `None` if it does not
+    /// exist
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut value = json!({"foo": ["bar", "baz"]});
+    /// let path: NormalizedPath = "$['foo'][1]".parse()?;
+    /// if let Some(node) = path.resolve_mut(&mut value) {
+    ///     *node = json!("bop");
+    /// }
+    /// assert_eq!(value, json!({"foo": ["bar", "bop"]}));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.try_resolve_mut(value).ok()
+    }
+
+    /// Get a reference to the node at this location in `value`, or an error describing which
+    /// element of the path could not be resolved
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let value = json!({"foo": {"bar": "baz"}});
+    /// let path: NormalizedPath = "$['foo'][0]".parse()?;
+    /// let error = path.try_resolve(&value).unwrap_err();
+    /// assert_eq!(error.to_string(), "expected an array at $['foo'], but found an object");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_resolve<'v>(&self, value: &'v Value) -> Result<&'v Value, ResolveError> {
+        self.0
+            .iter()
+            .enumerate()
+            .try_fold(value, |node, (i, element)| {
+                element.step(node).ok_or_else(|| self.error_at(i, node))
+            })
+    }
+
+    /// Get a mutable reference to the node at this location in `value`, or an error describing
+    /// which element of the path could not be resolved
+    pub fn try_resolve_mut<'v>(&self, value: &'v mut Value) -> Result<&'v mut Value, ResolveError> {
+        self.resolve_prefix_mut(self.len(), value)
+    }
+
+    /// Remove the node at this location from `value`, and return it
+    ///
+    /// Removing an element from an array will shift any subsequent elements to the left. The
+    /// root node can not be removed, so this will produce an error if the path is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut value = json!({"foo": ["bar", "baz"]});
+    /// let path: NormalizedPath = "$['foo'][0]".parse()?;
+    /// assert_eq!(path.remove(&mut value)?, "bar");
+    /// assert_eq!(value, json!({"foo": ["baz"]}));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remove(&self, value: &mut Value) -> Result<Value, ResolveError> {
+        let Some(element) = self.last() else {
+            return Err(ResolveError::RemoveRoot);
+        };
+        let parent = self.resolve_prefix_mut(self.len() - 1, value)?;
+        let removed = match (element, &mut *parent) {
+            (PathElement::Name(name), Value::Object(map)) => map.remove(name.as_ref()),
+            (PathElement::Index(index), Value::Array(list)) if *index < list.len() => {
+                Some(list.remove(*index))
+            }
+            _ => None,
+        };
+        removed.ok_or_else(|| self.error_at(self.len() - 1, parent))
+    }
+
+    /// Insert `node` at this location in `value`, returning the node it replaced, if any
+    ///
+    /// Inserting into an object will replace an existing member of the same name. Inserting into
+    /// an array will shift the element at the given index, and any subsequent elements, to the
+    /// right; the index can be equal to the length of the array, to append to it. This is
+    /// consistent with the JSON Patch `add` operation.
+    ///
+    /// If the path is empty, then `value` is replaced by `node`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use serde_json_path::NormalizedPath;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut value = json!({"foo": ["bar"]});
+    /// let path: NormalizedPath = "$['foo'][1]".parse()?;
+    /// assert_eq!(path.insert(&mut value, json!("baz"))?, None);
+    /// let path: NormalizedPath = "$['bop']".parse()?;
+    /// assert_eq!(path.insert(&mut value, json!(true))?, None);
+    /// assert_eq!(value, json!({"foo": ["bar", "baz"], "bop": true}));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn insert(&self, value: &mut Value, node: Value) -> Result<Option<Value>, ResolveError> {
+        let Some(element) = self.last() else {
+            return Ok(Some(std::mem::replace(value, node)));
+        };
+        let parent = self.resolve_prefix_mut(self.len() - 1, value)?;
+        match (element, &mut *parent) {
+            (PathElement::Name(name), Value::Object(map)) => Ok(map.insert(name.to_string(), node)),
+            (PathElement::Index(index), Value::Array(list)) if *index <= list.len() => {
+                list.insert(*index, node);
+                Ok(None)
+            }
+            _ => Err(self.error_at(self.len() - 1, parent)),
+        }
+    }
+
+    /// Resolve the node located by the first `len` elements of the path
+    fn resolve_prefix_mut<'v>(
+        &self,
+        len: usize,
+        value: &'v mut Value,
+    ) -> Result<&'v mut Value, ResolveError> {
+        self.0[..len]
+            .iter()
+            .enumerate()
+            .try_fold(value, |node, (i, element)| {
+                if element.step(node).is_none() {
+                    return Err(self.error_at(i, node));
+                }
+                Ok(element.step_mut(node).expect("element exists"))
+            })
+    }
+
+    /// Produce the error for when the element at index `i` could not be found in `node`
+    fn error_at(&self, i: usize, node: &Value) -> ResolveError {
+        let parent = self.0[..i]
+            .iter()
+            .cloned()
+            .map(PathElement::into_owned)
+            .collect();
+        match (&self.0[i], node) {
+            (PathElement::Name(name), Value::Object(_)) => ResolveError::MissingMember {
+                parent,
+                name: name.to_string(),
+            },
+            (PathElement::Index(index), Value::Array(list)) => ResolveError::IndexOutOfBounds {
+                parent,
+                index: *index,
+                len: list.len(),
+            },
+            (element, node) => ResolveError::TypeMismatch {
+                parent,
+                expected: if element.is_name() {
+                    "an object"
+                } else {
+                    "an array"
+                },
+                found: match node {
+                    Value::Null => "null",
+                    Value::Bool(_) => "a boolean",
+                    Value::Number(_) => "a number",
+                    Value::String(_) => "a string",
+                    Value::Array(_) => "an array",
+                    Value::Object(_) => "an object",
+                },
+            },
+        }
+    }
 }
 
 impl<'a> IntoIterator for NormalizedPath<'a> {
@@ -170,6 +511,18 @@ impl<'a> IntoIterator for NormalizedPath<'a> {
     }
 }
 
+impl<'a> FromIterator<PathElement<'a>> for NormalizedPath<'a> {
+    fn from_iter<T: IntoIterator<Item = PathElement<'a>>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'a> From<Vec<PathElement<'a>>> for NormalizedPath<'a> {
+    fn from(elements: Vec<PathElement<'a>>) -> Self {
+        Self(elements)
+    }
+}
+
 impl Display for NormalizedPath<'_> {
     /// Format the [`NormalizedPath`] as a JSONPath string using the canonical bracket notation
     /// as per the [JSONPath Specification][norm-paths]
@@ -192,7 +545,7 @@ impl Display for NormalizedPath<'_> {
         write!(f, "$")?;
         for elem in &self.0 {
             match elem {
-                PathElement::Name(name) => write!(f, "['{name}']")?,
+                PathElement::Name(_) => write!(f, "['{elem}']")?,
                 PathElement::Index(index) => write!(f, "[{index}]")?,
             }
         }
@@ -200,6 +553,36 @@ impl Display for NormalizedPath<'_> {
     }
 }
 
+impl FromStr for NormalizedPath<'static> {
+    type Err = ParseNormalizedPathError;
+
+    /// Parse a [`NormalizedPath`] from its canonical string representation
+    ///
+    /// The string must strictly adhere to the [Normalized Path grammar][norm-paths] from the
+    /// JSONPath specification, i.e., it must be in the form produced by the
+    /// [`Display`][NormalizedPath#impl-Display-for-NormalizedPath%3C'_%3E] implementation.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use serde_json_path::{NormalizedPath, ParseNormalizedPathError};
+    /// # fn main() -> Result<(), ParseNormalizedPathError> {
+    /// let path: NormalizedPath = "$['foo'][0]['it\\'s']".parse()?;
+    /// assert_eq!(path.len(), 3);
+    /// assert!(path.last().is_some_and(|e| e == "it's"));
+    /// // only the canonical form is accepted:
+    /// assert!("$.foo[0]".parse::<NormalizedPath>().is_err());
+    /// assert!("$[\"foo\"]".parse::<NormalizedPath>().is_err());
+    /// assert!("$['foo'][01]".parse::<NormalizedPath>().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [norm-paths]: https://www.rfc-editor.org/rfc/rfc9535.html#name-normalized-paths
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NormalizedPathParser::new(s).parse()
+    }
+}
+
 impl Serialize for NormalizedPath<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -209,11 +592,222 @@ impl Serialize for NormalizedPath<'_> {
     }
 }
 
+impl<'de> Deserialize<'de> for NormalizedPath<'static> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct NormalizedPathVisitor;
+
+        impl Visitor<'_> for NormalizedPathVisitor {
+            type Value = NormalizedPath<'static>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(formatter, "a string representing a normalized path")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_str(NormalizedPathVisitor)
+    }
+}
+
+/// Error produced when a [`NormalizedPath`] can not be resolved against a [`serde_json::Value`]
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub enum ResolveError {
+    /// The object at `parent` does not contain a member with the given `name`
+    #[error("the object at {parent} has no member '{name}'")]
+    MissingMember {
+        /// The location of the object
+        parent: NormalizedPath<'static>,
+        /// The name of the missing member
+        name: String,
+    },
+    /// The array at `parent` does not contain an element at the given `index`
+    #[error("index {index} is out of bounds for the array at {parent}, of length {len}")]
+    IndexOutOfBounds {
+        /// The location of the array
+        parent: NormalizedPath<'static>,
+        /// The index that was out of bounds
+        index: usize,
+        /// The length of the array
+        len: usize,
+    },
+    /// The node at `parent` is not an object, for a name element, or an array, for an index
+    /// element
+    #[error("expected {expected} at {parent}, but found {found}")]
+    TypeMismatch {
+        /// The location of the node
+        parent: NormalizedPath<'static>,
+        /// The kind of node that was expected, i.e., `"an object"` or `"an array"`
+        expected: &'static str,
+        /// The kind of node that was found
+        found: &'static str,
+    },
+    /// An attempt was made to remove the root node
+    #[error("the root node can not be removed")]
+    RemoveRoot,
+}
+
+/// Error produced when parsing an invalid [`NormalizedPath`] string
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+#[error("at position {position}, {message}")]
+pub struct ParseNormalizedPathError {
+    position: usize,
+    message: &'static str,
+}
+
+impl ParseNormalizedPathError {
+    /// Get the 1-indexed error position
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the error message
+    pub fn message(&self) -> &str {
+        self.message
+    }
+}
+
+/// Parser for the [Normalized Path grammar][norm-paths]
+///
+/// [norm-paths]: https://www.rfc-editor.org/rfc/rfc9535.html#section-2.7
+struct NormalizedPathParser<'s> {
+    input: &'s str,
+    chars: Peekable<CharIndices<'s>>,
+}
+
+impl<'s> NormalizedPathParser<'s> {
+    fn new(input: &'s str) -> Self {
+        Self {
+            input,
+            chars: input.char_indices().peekable(),
+        }
+    }
+
+    fn error(&self, index: Option<usize>, message: &'static str) -> ParseNormalizedPathError {
+        ParseNormalizedPathError {
+            position: index.unwrap_or(self.input.len()) + 1,
+            message,
+        }
+    }
+
+    fn next_index(&mut self) -> Option<usize> {
+        self.chars.peek().map(|(i, _)| *i)
+    }
+
+    fn expect(
+        &mut self,
+        expected: char,
+        message: &'static str,
+    ) -> Result<(), ParseNormalizedPathError> {
+        match self.chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((i, _)) => Err(self.error(Some(i), message)),
+            None => Err(self.error(None, message)),
+        }
+    }
+
+    fn parse(mut self) -> Result<NormalizedPath<'static>, ParseNormalizedPathError> {
+        self.expect('$', "expected root identifier '$'")?;
+        let mut path = NormalizedPath::default();
+        while self.chars.peek().is_some() {
+            self.expect('[', "expected '['")?;
+            let element = match self.chars.peek().copied() {
+                Some((_, '\'')) => self.name()?,
+                Some((_, '0'..='9')) => self.index()?,
+                Some((i, _)) => return Err(self.error(Some(i), "expected name or index selector")),
+                None => return Err(self.error(None, "expected name or index selector")),
+            };
+            self.expect(']', "expected ']'")?;
+            path.push(element);
+        }
+        Ok(path)
+    }
+
+    fn index(&mut self) -> Result<PathElement<'static>, ParseNormalizedPathError> {
+        let start = self.next_index().expect("index starts with a digit");
+        if let Some((_, '0')) = self.chars.next() {
+            if let Some((i, '0'..='9')) = self.chars.peek().copied() {
+                return Err(self.error(Some(i), "leading zeros are not permitted in indices"));
+            }
+            return Ok(PathElement::Index(0));
+        }
+        while let Some((_, '0'..='9')) = self.chars.peek() {
+            self.chars.next();
+        }
+        let end = self.next_index().unwrap_or(self.input.len());
+        self.input[start..end]
+            .parse::<Integer>()
+            .ok()
+            .and_then(|i| usize::try_from(i).ok())
+            .map(PathElement::Index)
+            .ok_or_else(|| self.error(Some(start), "index out of valid range"))
+    }
+
+    fn name(&mut self) -> Result<PathElement<'static>, ParseNormalizedPathError> {
+        self.expect('\'', "expected '\''")?;
+        let mut name = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '\'')) => break,
+                Some((i, '\\')) => name.push(self.escaped(i)?),
+                Some((i, c)) if c < '\u{20}' => {
+                    return Err(self.error(Some(i), "control characters must be escaped"))
+                }
+                Some((_, c)) => name.push(c),
+                None => return Err(self.error(None, "unterminated name selector")),
+            }
+        }
+        Ok(PathElement::Name(Cow::Owned(name)))
+    }
+
+    /// Parse the escape sequence following a `\` found at `start`
+    ///
+    /// Only the escape sequences permitted in normalized paths are accepted, i.e., those produced
+    /// by the [`Display`] implementation of [`PathElement`].
+    fn escaped(&mut self, start: usize) -> Result<char, ParseNormalizedPathError> {
+        let c = match self.chars.next() {
+            Some((_, 'b')) => '\u{0008}',
+            Some((_, 'f')) => '\u{000C}',
+            Some((_, 'n')) => '\u{000A}',
+            Some((_, 'r')) => '\u{000D}',
+            Some((_, 't')) => '\u{0009}',
+            Some((_, '\'')) => '\'',
+            Some((_, '\\')) => '\\',
+            Some((_, 'u')) => {
+                let hex: String = self.chars.by_ref().take(4).map(|(_, c)| c).collect();
+                // "00"-"07", "0b", "0e"-"0f", "10"-"1f"
+                let valid = matches!(
+                    hex.as_bytes(),
+                    [b'0', b'0', b'0', b'0'..=b'7' | b'b' | b'e' | b'f']
+                        | [b'0', b'0', b'1', b'0'..=b'9' | b'a'..=b'f']
+                );
+                if !valid {
+                    return Err(self.error(Some(start), "invalid unicode escape sequence"));
+                }
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .expect("valid control character")
+            }
+            _ => return Err(self.error(Some(start), "invalid escape sequence")),
+        };
+        Ok(c)
+    }
+}
+
 /// An element within a [`NormalizedPath`]
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub enum PathElement<'a> {
     /// A key within a JSON object
-    Name(&'a str),
+    Name(Cow<'a, str>),
     /// An index of a JSON Array
     Index(usize),
 }
@@ -229,7 +823,7 @@ impl PathElement<'_> {
     /// Get the underlying name if the [`PathElement`] is `Name`, or `None` otherwise
     pub fn as_name(&self) -> Option<&str> {
         match self {
-            PathElement::Name(n) => Some(n),
+            PathElement::Name(n) => Some(n.as_ref()),
             PathElement::Index(_) => None,
         }
     }
@@ -251,6 +845,32 @@ impl PathElement<'_> {
     pub fn is_index(&self) -> bool {
         self.as_index().is_some()
     }
+
+    /// Get the child of `node` located by this element
+    fn step<'v>(&self, node: &'v Value) -> Option<&'v Value> {
+        match (self, node) {
+            (PathElement::Name(name), Value::Object(map)) => map.get(name.as_ref()),
+            (PathElement::Index(index), Value::Array(list)) => list.get(*index),
+            _ => None,
+        }
+    }
+
+    /// Get the child of `node` located by this element, mutably
+    fn step_mut<'v>(&self, node: &'v mut Value) -> Option<&'v mut Value> {
+        match (self, node) {
+            (PathElement::Name(name), Value::Object(map)) => map.get_mut(name.as_ref()),
+            (PathElement::Index(index), Value::Array(list)) => list.get_mut(*index),
+            _ => None,
+        }
+    }
+
+    /// Convert into an owned [`PathElement`] that does not borrow from the queried value
+    pub fn into_owned(self) -> PathElement<'static> {
+        match self {
+            PathElement::Name(n) => PathElement::Name(Cow::Owned(n.into_owned())),
+            PathElement::Index(i) => PathElement::Index(i),
+        }
+    }
 }
 
 impl PartialOrd for PathElement<'_> {
@@ -266,7 +886,7 @@ impl PartialOrd for PathElement<'_> {
 impl PartialEq<str> for PathElement<'_> {
     fn eq(&self, other: &str) -> bool {
         match self {
-            PathElement::Name(s) => s.eq(&other),
+            PathElement::Name(s) => s == other,
             PathElement::Index(_) => false,
         }
     }
@@ -275,7 +895,7 @@ impl PartialEq<str> for PathElement<'_> {
 impl PartialEq<&str> for PathElement<'_> {
     fn eq(&self, other: &&str) -> bool {
         match self {
-            PathElement::Name(s) => s.eq(other),
+            PathElement::Name(s) => s == other,
             PathElement::Index(_) => false,
         }
     }
@@ -303,10 +923,10 @@ impl Display for PathElement<'_> {
                         '\u{000D}' => write!(f, r#"\r"#)?, // r CR carriage return
                         '\u{0009}' => write!(f, r#"\t"#)?, // t HT horizontal tab
                         '\u{0027}' => write!(f, r#"\'"#)?, // ' apostrophe
-                        '\u{005C}' => write!(f, r#"\"#)?,  // \ backslash (reverse solidus)
-                        ('\x00'..='\x07') | '\x0b' | '\x0e' | '\x0f' => {
-                            // "00"-"07", "0b", "0e"-"0f"
-                            write!(f, "\\u000{:x}", c as i32)?
+                        '\u{005C}' => write!(f, r#"\\"#)?, // \ backslash (reverse solidus)
+                        ('\x00'..='\x07') | '\x0b' | ('\x0e'..='\x1f') => {
+                            // "00"-"07", "0b", "0e"-"1f"
+                            write!(f, "\\u00{:02x}", c as u32)?
                         }
                         _ => write!(f, "{c}")?,
                     }
@@ -318,9 +938,21 @@ impl Display for PathElement<'_> {
     }
 }
 
+impl<'a> From<&'a str> for PathElement<'a> {
+    fn from(s: &'a str) -> Self {
+        Self::Name(Cow::Borrowed(s))
+    }
+}
+
 impl<'a> From<&'a String> for PathElement<'a> {
     fn from(s: &'a String) -> Self {
-        Self::Name(s.as_str())
+        Self::Name(Cow::Borrowed(s.as_str()))
+    }
+}
+
+impl From<String> for PathElement<'static> {
+    fn from(s: String) -> Self {
+        Self::Name(Cow::Owned(s))
     }
 }
 
@@ -344,24 +976,22 @@ impl Serialize for PathElement<'_> {
 
 #[cfg(test)]
 mod tests {
-    use super::{NormalizedPath, PathElement};
+    use serde_json::json;
+
+    use super::{NormalizedPath, PathElement, ResolveError};
 
     #[test]
     fn normalized_path_to_json_pointer() {
-        let np = NormalizedPath(vec![
-            PathElement::Name("foo"),
-            PathElement::Index(42),
-            PathElement::Name("bar"),
-        ]);
+        let np = NormalizedPath(vec!["foo".into(), PathElement::Index(42), "bar".into()]);
         assert_eq!(np.to_json_pointer(), "/foo/42/bar");
     }
 
     #[test]
     fn normalized_path_to_json_pointer_with_escapes() {
         let np = NormalizedPath(vec![
-            PathElement::Name("foo~bar"),
+            "foo~bar".into(),
             PathElement::Index(42),
-            PathElement::Name("baz/bop"),
+            "baz/bop".into(),
         ]);
         assert_eq!(np.to_json_pointer(), "/foo~0bar/42/baz~1bop");
     }
@@ -369,33 +999,204 @@ mod tests {
     #[test]
     fn normalized_element_fmt() {
         for (name, elem, exp) in [
-            ("simple name", PathElement::Name("foo"), "foo"),
+            ("simple name", PathElement::from("foo"), "foo"),
             ("index", PathElement::Index(1), "1"),
-            ("escape_apostrophes", PathElement::Name("'hi'"), r#"\'hi\'"#),
+            ("escape_apostrophes", PathElement::from("'hi'"), r#"\'hi\'"#),
             (
                 "escapes",
-                PathElement::Name(r#"'\b\f\n\r\t\\'"#),
-                r#"\'\b\f\n\r\t\\\'"#,
+                PathElement::from(r#"'\b\f\n\r\t\\'"#),
+                r#"\'\\b\\f\\n\\r\\t\\\\\'"#,
+            ),
+            (
+                "escape_control_characters",
+                PathElement::from("\u{0008}\u{000C}\u{000A}\u{000D}\u{0009}"),
+                r#"\b\f\n\r\t"#,
             ),
             (
                 "escape_vertical_unicode",
-                PathElement::Name("\u{000B}"),
+                PathElement::from("\u{000B}"),
                 r#"\u000b"#,
             ),
             (
                 "escape_unicode_null",
-                PathElement::Name("\u{0000}"),
+                PathElement::from("\u{0000}"),
                 r#"\u0000"#,
             ),
             (
                 "escape_unicode_runes",
-                PathElement::Name(
+                PathElement::from(
                     "\u{0001}\u{0002}\u{0003}\u{0004}\u{0005}\u{0006}\u{0007}\u{000e}\u{000F}",
                 ),
                 r#"\u0001\u0002\u0003\u0004\u0005\u0006\u0007\u000e\u000f"#,
             ),
+            (
+                "escape_unicode_runes_1x",
+                PathElement::from("\u{0010}\u{001a}\u{001F}"),
+                r#"\u0010\u001a\u001f"#,
+            ),
         ] {
             assert_eq!(exp, elem.to_string(), "{name}");
         }
     }
+
+    #[test]
+    fn normalized_path_parse() {
+        for (input, exp) in [
+            ("$", NormalizedPath::default()),
+            ("$[0]", NormalizedPath(vec![PathElement::Index(0)])),
+            (
+                "$['foo'][42]['']",
+                NormalizedPath(vec!["foo".into(), PathElement::Index(42), "".into()]),
+            ),
+            (
+                r#"$['\'\\\b\f\n\r\t\u0000\u000b\u001f"☺']"#,
+                NormalizedPath(vec![
+                    "'\\\u{0008}\u{000C}\n\r\t\u{0000}\u{000B}\u{001F}\"☺".into()
+                ]),
+            ),
+        ] {
+            let path: NormalizedPath<'static> = input.parse().expect(input);
+            assert_eq!(path, exp, "{input}");
+            assert_eq!(path.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn normalized_path_parse_errors() {
+        for (input, position) in [
+            ("", 1),
+            ("foo", 1),
+            ("$.foo", 2),
+            ("$[foo]", 3),
+            ("$[\"foo\"]", 3),
+            ("$['foo'", 8),
+            ("$['foo", 7),
+            ("$['foo']]", 9),
+            ("$[01]", 4),
+            ("$[-1]", 3),
+            ("$[9007199254740992]", 3),
+            ("$[ 0]", 3),
+            ("$['\u{0009}']", 4),
+            (r#"$['\/']"#, 4),
+            (r#"$['\"']"#, 4),
+            (r#"$['\u0008']"#, 4),
+            (r#"$['\u000B']"#, 4),
+            (r#"$['\u00"#, 4),
+        ] {
+            let err = input
+                .parse::<NormalizedPath>()
+                .expect_err(&format!("{input:?} should fail"));
+            assert_eq!(err.position(), position, "{input:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn path_algebra() {
+        let path: NormalizedPath = "$['a'][1]['b']".parse().unwrap();
+        let prefix: NormalizedPath = "$['a'][1]".parse().unwrap();
+        let other: NormalizedPath = "$['a'][2]".parse().unwrap();
+        let root = NormalizedPath::default();
+        assert_eq!(path.depth(), 3);
+        assert_eq!(root.depth(), 0);
+        assert_eq!(path.parent(), Some(prefix.clone()));
+        assert!(path.starts_with(&root));
+        assert!(path.starts_with(&prefix));
+        assert!(!path.starts_with(&other));
+        assert!(!prefix.starts_with(&path));
+        let rest = path.strip_prefix(&prefix).unwrap();
+        assert_eq!(rest, NormalizedPath(vec!["b".into()]));
+        assert_eq!(prefix.join(&rest), path);
+        assert_eq!(path.strip_prefix(&root), Some(path.clone()));
+        assert_eq!(path.strip_prefix(&path), Some(root.clone()));
+        assert_eq!(path.strip_prefix(&other), None);
+        assert_eq!(path.common_prefix(&other).to_string(), "$['a']");
+        assert_eq!(path.common_prefix(&prefix), prefix);
+        assert_eq!(path.common_prefix(&root), root);
+    }
+
+    #[test]
+    fn resolve_errors() {
+        let value = json!({"foo": [1, {"bar": null}]});
+        for (path, exp) in [
+            (
+                "$['baz']",
+                ResolveError::MissingMember {
+                    parent: NormalizedPath::default(),
+                    name: "baz".to_owned(),
+                },
+            ),
+            (
+                "$['foo'][2]",
+                ResolveError::IndexOutOfBounds {
+                    parent: NormalizedPath(vec!["foo".into()]),
+                    index: 2,
+                    len: 2,
+                },
+            ),
+            (
+                "$['foo'][1]['bar']['baz']",
+                ResolveError::TypeMismatch {
+                    parent: NormalizedPath(vec!["foo".into(), PathElement::Index(1), "bar".into()]),
+                    expected: "an object",
+                    found: "null",
+                },
+            ),
+        ] {
+            let path: NormalizedPath = path.parse().unwrap();
+            assert_eq!(path.try_resolve(&value), Err(exp.clone()), "{path}");
+            assert_eq!(path.try_resolve_mut(&mut value.clone()), Err(exp), "{path}");
+        }
+    }
+
+    #[test]
+    fn remove_and_insert() {
+        let mut value = json!({"foo": [1, 2, 3]});
+        let path: NormalizedPath = "$['foo'][0]".parse().unwrap();
+        assert_eq!(path.remove(&mut value), Ok(json!(1)));
+        assert_eq!(path.insert(&mut value, json!(0)), Ok(None));
+        assert_eq!(value, json!({"foo": [0, 2, 3]}));
+
+        let path: NormalizedPath = "$['foo'][4]".parse().unwrap();
+        assert!(matches!(
+            path.insert(&mut value, json!(4)),
+            Err(ResolveError::IndexOutOfBounds {
+                index: 4,
+                len: 3,
+                ..
+            })
+        ));
+        let path: NormalizedPath = "$['foo'][3]".parse().unwrap();
+        assert_eq!(path.insert(&mut value, json!(4)), Ok(None));
+        assert_eq!(path.resolve(&value), Some(&json!(4)));
+
+        let path: NormalizedPath = "$['foo']".parse().unwrap();
+        assert_eq!(
+            path.insert(&mut value, json!("bar")),
+            Ok(Some(json!([0, 2, 3, 4])))
+        );
+        assert_eq!(path.remove(&mut value), Ok(json!("bar")));
+        assert!(matches!(
+            path.remove(&mut value),
+            Err(ResolveError::MissingMember { .. })
+        ));
+        assert_eq!(
+            NormalizedPath::default().remove(&mut value),
+            Err(ResolveError::RemoveRoot)
+        );
+        assert_eq!(
+            NormalizedPath::default().insert(&mut value, json!(null)),
+            Ok(Some(json!({})))
+        );
+        assert_eq!(value, json!(null));
+    }
+
+    #[test]
+    fn normalized_path_serde_round_trip() {
+        let path: NormalizedPath = r#"$['a\'b'][1]"#.parse().unwrap();
+        let value = serde_json::to_value(&path).unwrap();
+        assert_eq!(value, r#"$['a\'b'][1]"#);
+        let path_de: NormalizedPath = serde_json::from_value(value).unwrap();
+        assert_eq!(path, path_de);
+        assert!(serde_json::from_value::<NormalizedPath>("$.a".into()).is_err());
+    }
 }
@@ -134,15 +134,129 @@
 //! $[?value(@..color) == "red"]
 //! ```
 //!
+//! # Non-standard Functions
+//!
+//! The following functions are not defined in the IETF JSONPath Specification, and are enabled
+//! in `serde_json_path` using feature flags. Arguments of the wrong type, e.g., a number where a
+//! string is expected, produce nothing, or false for functions that produce a [`LogicalType`].
+//!
+//! ## String functions
+//!
+//! These are enabled by the `string-functions` feature. All arguments are [`ValueType`]s. Lengths
+//! and positions are measured in characters, as they are by `length`.
+//!
+//! | Function | Result | Description |
+//! |----------|--------|-------------|
+//! | `lower(s)` | [`ValueType`] | `s` in lowercase |
+//! | `upper(s)` | [`ValueType`] | `s` in uppercase |
+//! | `trim(s)` | [`ValueType`] | `s` without leading and trailing whitespace |
+//! | `starts_with(s, prefix)` | [`LogicalType`] | true if `s` starts with `prefix` |
+//! | `ends_with(s, suffix)` | [`LogicalType`] | true if `s` ends with `suffix` |
+//! | `contains(s, pattern)` | [`LogicalType`] | true if `s` contains `pattern` |
+//! | `substring(s, start, length?)` | [`ValueType`] | up to `length` characters of `s` from the integer `start`, which counts back from the end of `s` if negative |
+//! | `split_count(s, separator)` | [`ValueType`] | the number of parts `s` is split into by the non-empty `separator` |
+//! | `concat(s...)` | [`ValueType`] | all of its arguments joined together |
+//!
+//! ### Example
+//!
+//! ```text
+//! $[?starts_with(lower(@.name), 'adm')]
+//! ```
+//!
+//! ## Numeric functions
+//!
+//! These are enabled by the `numeric-functions` feature. Integers are handled exactly, as they are
+//! when compared, and the result is nothing if any of the values are not numbers.
+//!
+//! | Function | Result | Description |
+//! |----------|--------|-------------|
+//! | `sum(nodes)` | [`ValueType`] | the sum of the numbers in `nodes`, which is `0` if there are none |
+//! | `avg(nodes)` | [`ValueType`] | the mean of the numbers in `nodes`, which is nothing if there are none |
+//! | `min(nodes)` | [`ValueType`] | the least of the numbers in `nodes` |
+//! | `max(nodes)` | [`ValueType`] | the greatest of the numbers in `nodes` |
+//! | `abs(n)` | [`ValueType`] | the absolute value of `n` |
+//! | `floor(n)` | [`ValueType`] | the largest integer less than or equal to `n` |
+//! | `ceil(n)` | [`ValueType`] | the smallest integer greater than or equal to `n` |
+//! | `round(n, digits?)` | [`ValueType`] | `n` rounded to the nearest integer, or to `digits` decimal places |
+//!
+//! ### Example
+//!
+//! ```text
+//! $.invoices[?sum(@.lines[*].amount) > 1000]
+//! ```
+//!
+//! ## Type functions
+//!
+//! These are enabled by the `type-functions` feature. They can tell apart values that existence
+//! tests and comparisons cannot, e.g., a `null` and an array.
+//!
+//! | Function | Result | Description |
+//! |----------|--------|-------------|
+//! | `type(v)` | [`ValueType`] | the type of `v`, i.e., one of `'string'`, `'number'`, `'boolean'`, `'null'`, `'object'` or `'array'`, or nothing if `v` is nothing |
+//! | `is_string(v)` | [`LogicalType`] | true if `v` is a string |
+//! | `is_number(v)` | [`LogicalType`] | true if `v` is a number |
+//! | `is_boolean(v)` | [`LogicalType`] | true if `v` is `true` or `false` |
+//! | `is_null(v)` | [`LogicalType`] | true if `v` is `null` |
+//! | `is_object(v)` | [`LogicalType`] | true if `v` is an object |
+//! | `is_array(v)` | [`LogicalType`] | true if `v` is an array |
+//!
+//! ### Example
+//!
+//! ```text
+//! $.members[?is_array(@.value)]
+//! ```
+//!
+//! ## Location functions
+//!
+//! These are enabled by the `location-functions` feature of `serde_json_path`. They take no
+//! arguments, and produce the last element of the location of the node being tested by the
+//! filter, i.e., its member name or array index. Queries that call them track the location of
+//! every node they test, as [`EvalContext::track_locations`] does.
+//!
+//! | Function | Result | Description |
+//! |----------|--------|-------------|
+//! | `key()` | [`ValueType`] | the member name of the current node, or nothing if it is an array element |
+//! | `index()` | [`ValueType`] | the index of the current node, or nothing if it is an object member |
+//!
+//! ### Example
+//!
+//! ```text
+//! $.users[?search(key(), '^admin_')]
+//! $.items[?index() < 3]
+//! ```
+//!
+//! ## Date-time functions
+//!
+//! These are enabled by the `datetime` feature of `serde_json_path`. Date-times are [RFC 3339]
+//! strings, e.g., `'2024-01-01T00:00:00.5+02:00'`, which are compared as instants, regardless of
+//! their offsets. A value that is not a valid date-time produces nothing, or false. No time zone
+//! database is used, so only the offset written in the string is taken into account.
+//!
+//! | Function | Result | Description |
+//! |----------|--------|-------------|
+//! | `timestamp(a)` | [`ValueType`] | the number of seconds, including any fraction, from the Unix epoch to `a` |
+//! | `date_before(a, b)` | [`LogicalType`] | true if `a` is before `b` |
+//! | `date_diff(a, b, unit)` | [`ValueType`] | the time from `b` to `a`, in one of `'milliseconds'`, `'seconds'`, `'minutes'`, `'hours'`, `'days'` or `'weeks'` |
+//!
+//! [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+//!
+//! ### Example
+//!
+//! ```text
+//! $.events[?date_before('2024-01-01T00:00:00Z', @.ts)]
+//! ```
+//!
 use std::{
+    any::Any,
     collections::VecDeque,
+    error::Error,
     ops::{Deref, DerefMut},
     sync::LazyLock,
 };
 
-use serde_json::Value;
+use serde_json::{Number, Value};
 
-use crate::{node::NodeList, spec::query::Queryable};
+use crate::{context::EvalContext, node::NodeList, path::NormalizedPath, spec::query::Queryable};
 
 use super::{
     query::Query,
@@ -154,8 +268,84 @@ pub type Validator =
     LazyLock<Box<dyn Fn(&[FunctionExprArg]) -> Result<(), FunctionValidationError> + Send + Sync>>;
 
 #[doc(hidden)]
-pub type Evaluator =
-    LazyLock<Box<dyn for<'a> Fn(VecDeque<JsonPathValue<'a>>) -> JsonPathValue<'a> + Sync + Send>>;
+pub type Evaluator = LazyLock<
+    Box<
+        dyn for<'a> Fn(VecDeque<JsonPathValue<'a>>, &FunctionContext<'_, 'a>) -> JsonPathValue<'a>
+            + Sync
+            + Send,
+    >,
+>;
+
+/// The context in which a function is evaluated
+///
+/// Functions declared using the `#[function]` attribute macro can access this by declaring a
+/// parameter of type `&FunctionContext`. This parameter is not a JSONPath argument, so it is not
+/// passed in the query, and it can appear anywhere in the function's parameter list.
+///
+/// It provides access to the root node of the query, the current node, i.e., the node being
+/// tested by the filter that calls the function, and the [`Context`] supplied with the query.
+///
+/// The lifetime `'b` is that of the queried document. A function that returns a node obtained
+/// from the [`FunctionContext`], e.g., from its [`root`][FunctionContext::root], must tie that
+/// lifetime to its return type:
+/// ```rust,ignore
+/// #[serde_json_path::function]
+/// fn config<'a>(ctx: &FunctionContext<'_, 'a>, key: ValueType<'a>) -> ValueType<'a> {
+///     // ...
+/// }
+/// ```
+///
+/// The lifetime `'c` is that of the evaluation, for which the [`Context`] is borrowed, so data
+/// obtained from the [`Context`] can not be returned from the function.
+///
+/// [`Context`]: crate::context::Context
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionContext<'c, 'b> {
+    name: &'c str,
+    current: &'b Value,
+    eval: &'c EvalContext<'b, 'c>,
+}
+
+impl<'c, 'b> FunctionContext<'c, 'b> {
+    /// Get the root node of the query
+    pub fn root(&self) -> &'b Value {
+        self.eval.root()
+    }
+
+    /// Get the current node, i.e., `@`
+    pub fn current(&self) -> &'b Value {
+        self.current
+    }
+
+    /// Get the location of the current node
+    ///
+    /// This is only available when locations are being tracked, which `JsonPath` does for any
+    /// query that calls a function accepting a [`FunctionContext`].
+    pub fn location(&self) -> Option<&'c NormalizedPath<'b>> {
+        self.eval.location()
+    }
+
+    /// Get the value that the variable `name` is bound to in the [`Context`]
+    ///
+    /// [`Context`]: crate::context::Context
+    pub fn var(&self, name: &str) -> Option<&'c Value> {
+        self.eval.var(name)
+    }
+
+    /// Get the application data of type `T` from the [`Context`], if any
+    ///
+    /// [`Context`]: crate::context::Context
+    pub fn data<T: Any + Send + Sync>(&self) -> Option<&'c T> {
+        self.eval.context().and_then(|c| c.data())
+    }
+
+    /// Report an error produced by the function, which then produces nothing
+    #[doc(hidden)]
+    pub fn fail<'a>(&self, error: impl Into<Box<dyn Error + Send + Sync>>) -> JsonPathValue<'a> {
+        self.eval.report_error(self.name, error.into());
+        JsonPathValue::Nothing
+    }
+}
 
 #[doc(hidden)]
 #[allow(missing_debug_implementations)]
@@ -164,6 +354,9 @@ pub struct Function {
     pub result_type: FunctionArgType,
     pub validator: &'static Validator,
     pub evaluator: &'static Evaluator,
+    /// Whether the function accepts a [`FunctionContext`], and so may use the location of the
+    /// current node
+    pub uses_context: bool,
 }
 
 impl Function {
@@ -178,6 +371,15 @@ impl Function {
             result_type,
             evaluator,
             validator,
+            uses_context: false,
+        }
+    }
+
+    /// Mark the function as accepting a [`FunctionContext`]
+    pub const fn using_context(self) -> Self {
+        Self {
+            uses_context: true,
+            ..self
         }
     }
 }
@@ -396,6 +598,63 @@ where
     }
 }
 
+/// Conversion from a [`ValueType`] argument into a Rust type accepted by a function
+///
+/// This is used by the `#[function]` attribute macro for function parameters declared as Rust
+/// types, e.g., `&str` or `f64`. When the conversion fails, the function is not called, and
+/// instead produces nothing, unless the parameter is an `Option`, in which case it receives
+/// `None`.
+#[doc(hidden)]
+pub trait FromValueType<'v>: Sized {
+    fn from_value_type(value: &'v ValueType<'_>) -> Option<Self>;
+}
+
+impl<'v> FromValueType<'v> for &'v Value {
+    fn from_value_type(value: &'v ValueType<'_>) -> Option<Self> {
+        value.as_value()
+    }
+}
+
+impl<'v> FromValueType<'v> for &'v Number {
+    fn from_value_type(value: &'v ValueType<'_>) -> Option<Self> {
+        value.as_value().and_then(|v| match v {
+            Value::Number(n) => Some(n),
+            _ => None,
+        })
+    }
+}
+
+impl<'v> FromValueType<'v> for &'v str {
+    fn from_value_type(value: &'v ValueType<'_>) -> Option<Self> {
+        value.as_value().and_then(Value::as_str)
+    }
+}
+
+impl FromValueType<'_> for f64 {
+    fn from_value_type(value: &ValueType<'_>) -> Option<Self> {
+        value.as_value().and_then(Value::as_f64)
+    }
+}
+
+impl FromValueType<'_> for i64 {
+    fn from_value_type(value: &ValueType<'_>) -> Option<Self> {
+        let n = value.as_value().and_then(Value::as_number)?;
+        // numbers with no fractional part are integers, e.g., `1.0`, in the same way that
+        // they compare equal to integers:
+        n.as_i64().or_else(|| {
+            n.as_f64()
+                .filter(|f| f.fract() == 0.0 && *f >= i64::MIN as f64 && *f < i64::MAX as f64)
+                .map(|f| f as i64)
+        })
+    }
+}
+
+impl<'v, T: FromValueType<'v>> FromValueType<'v> for Option<T> {
+    fn from_value_type(value: &'v ValueType<'_>) -> Option<Self> {
+        Some(T::from_value_type(value))
+    }
+}
+
 #[doc(hidden)]
 #[derive(Debug)]
 pub enum JsonPathValue<'a> {
@@ -480,6 +739,8 @@ pub struct NotValidated;
 #[derive(Clone)]
 pub struct Validated {
     pub evaluator: &'static Evaluator,
+    /// Whether the function accepts a [`FunctionContext`]
+    pub uses_context: bool,
 }
 
 impl PartialEq for Validated {
@@ -499,21 +760,33 @@ impl std::fmt::Debug for Validated {
 }
 
 impl FunctionExpr<Validated> {
+    pub fn evaluate<'a, 'b: 'a>(
+        &'a self,
+        current: &'b Value,
+        root: &'b Value,
+    ) -> JsonPathValue<'a> {
+        self.evaluate_in(current, &EvalContext::new(root))
+    }
+
     #[cfg_attr(
         feature = "trace",
         tracing::instrument(name = "Evaluate Function Expr", level = "trace", parent = None, ret)
     )]
-    pub fn evaluate<'a, 'b: 'a>(
+    pub fn evaluate_in<'a, 'b: 'a>(
         &'a self,
         current: &'b Value,
-        root: &'b Value,
+        ctx: &EvalContext<'b, 'a>,
     ) -> JsonPathValue<'a> {
-        let args: VecDeque<JsonPathValue> = self
-            .args
-            .iter()
-            .map(|a| a.evaluate(current, root))
-            .collect();
-        (self.validated.evaluator)(args)
+        let args: VecDeque<JsonPathValue> =
+            self.args.iter().map(|a| a.evaluate(current, ctx)).collect();
+        (self.validated.evaluator)(
+            args,
+            &FunctionContext {
+                name: &self.name,
+                current,
+                eval: ctx,
+            },
+        )
     }
 }
 
@@ -531,6 +804,7 @@ impl FunctionExpr<NotValidated> {
                     return_type: f.result_type,
                     validated: Validated {
                         evaluator: f.evaluator,
+                        uses_context: f.uses_context,
                     },
                 });
             }
@@ -580,19 +854,25 @@ impl FunctionExprArg {
         feature = "trace",
         tracing::instrument(name = "Evaluate Function Arg", level = "trace", parent = None, ret)
     )]
-    fn evaluate<'a, 'b: 'a>(&'a self, current: &'b Value, root: &'b Value) -> JsonPathValue<'a> {
+    fn evaluate<'a, 'b: 'a>(
+        &'a self,
+        current: &'b Value,
+        ctx: &EvalContext<'b, 'a>,
+    ) -> JsonPathValue<'a> {
         match self {
             FunctionExprArg::Literal(lit) => lit.into(),
-            FunctionExprArg::SingularQuery(q) => match q.eval_query(current, root) {
+            FunctionExprArg::SingularQuery(q) => match q.eval_query_in(current, ctx) {
                 Some(n) => JsonPathValue::Node(n),
                 None => JsonPathValue::Nothing,
             },
-            FunctionExprArg::FilterQuery(q) => JsonPathValue::Nodes(q.query(current, root).into()),
-            FunctionExprArg::LogicalExpr(l) => match l.test_filter(current, root) {
+            FunctionExprArg::FilterQuery(q) => {
+                JsonPathValue::Nodes(q.query_in(current, ctx).into())
+            }
+            FunctionExprArg::LogicalExpr(l) => match l.test_filter_in(current, ctx) {
                 true => JsonPathValue::Logical(LogicalType::True),
                 false => JsonPathValue::Logical(LogicalType::False),
             },
-            FunctionExprArg::FunctionExpr(f) => f.evaluate(current, root),
+            FunctionExprArg::FunctionExpr(f) => f.evaluate_in(current, ctx),
         }
     }
 
@@ -612,42 +892,12 @@ impl FunctionExprArg {
                 }
             }
             FunctionExprArg::LogicalExpr(_) => Ok(FunctionArgType::Logical),
-            FunctionExprArg::FunctionExpr(func) => {
-                for f in inventory::iter::<Function> {
-                    if f.name == func.name.as_str() {
-                        return Ok(f.result_type);
-                    }
-                }
-                registered_function_result_type(&func.name).ok_or_else(|| {
-                    FunctionValidationError::Undefined {
-                        name: func.name.to_owned(),
-                    }
-                })
-            }
+            // the function was validated when it was parsed, which determined its return type:
+            FunctionExprArg::FunctionExpr(func) => Ok(func.return_type),
         }
     }
 }
 
-/// Get the result type of a registered function
-///
-/// This is a hack, but must be done, because the REGISTRY defined in `serde_json_path`
-/// is not accessible here. Therefore, this must also be maintained when new functions
-/// are added to the registry in the standard.
-///
-/// An alternative would be to define the registry of functions in core, we would then
-/// just not have the convenience macro for defining them, along with their validators
-/// and evaluators.
-fn registered_function_result_type(name: &str) -> Option<FunctionArgType> {
-    match name {
-        "length" => Some(FunctionArgType::Value),
-        "count" => Some(FunctionArgType::Value),
-        "match" => Some(FunctionArgType::Logical),
-        "search" => Some(FunctionArgType::Logical),
-        "value" => Some(FunctionArgType::Value),
-        _ => None,
-    }
-}
-
 /// Function argument types
 ///
 /// This is used to describe the type of a function argument to determine if it will be valid as a
@@ -721,6 +971,26 @@ pub enum FunctionValidationError {
         /// Received number of arguments
         received: usize,
     },
+    /// Fewer function arguments than the function requires
+    #[error("in function {name}, expected at least {minimum} args, but received {received}")]
+    TooFewArgs {
+        /// Function name
+        name: String,
+        /// Minimum number of arguments
+        minimum: usize,
+        /// Received number of arguments
+        received: usize,
+    },
+    /// More function arguments than the function accepts
+    #[error("in function {name}, expected at most {maximum} args, but received {received}")]
+    TooManyArgs {
+        /// Function name
+        name: String,
+        /// Maximum number of arguments
+        maximum: usize,
+        /// Received number of arguments
+        received: usize,
+    },
     /// The type of received argument does not match the function definition
     #[error("in function {name}, in argument position {position}, expected a type that converts to {expected}, received {received}")]
     MismatchTypeKind {
@@ -742,12 +1012,12 @@ impl TestFilter for FunctionExpr<Validated> {
         feature = "trace",
         tracing::instrument(name = "Test Function Expr", level = "trace", parent = None, ret)
     )]
-    fn test_filter<'b>(&self, current: &'b Value, root: &'b Value) -> bool {
-        match self.evaluate(current, root) {
+    fn test_filter_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> bool {
+        match self.evaluate_in(current, ctx) {
             JsonPathValue::Nodes(nl) => !nl.is_empty(),
-            JsonPathValue::Value(v) => v.test_filter(current, root),
+            JsonPathValue::Value(v) => v.test_filter_in(current, ctx),
             JsonPathValue::Logical(l) => l.into(),
-            JsonPathValue::Node(n) => n.test_filter(current, root),
+            JsonPathValue::Node(n) => n.test_filter_in(current, ctx),
             JsonPathValue::Nothing => false,
         }
     }
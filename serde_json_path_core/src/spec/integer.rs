@@ -30,6 +30,10 @@ fn check_i64_is_valid(v: i64) -> bool {
 impl Integer {
     /// An [`Integer`] with the value 0
     pub const ZERO: Self = Self(0);
+    /// The largest valid [`Integer`], (2<sup>53</sup>)-1
+    pub const MAX: Self = Self(MAX);
+    /// The smallest valid [`Integer`], -(2<sup>53</sup>)+1
+    pub const MIN: Self = Self(MIN);
 
     fn try_new(value: i64) -> Result<Self, IntegerError> {
         if check_i64_is_valid(value) {
@@ -1,6 +1,7 @@
 //! Types representing the IETF JSONPath Standard
 pub mod functions;
 pub mod integer;
+pub mod number;
 pub mod query;
 pub mod segment;
 pub mod selector;
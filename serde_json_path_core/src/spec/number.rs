@@ -0,0 +1,264 @@
+//! Exact handling of JSON numbers
+//!
+//! JSON numbers that are integers are handled exactly, across the full range of both `i64` and
+//! `u64`, while other numbers are handled as 64-bit floating point numbers. This is used when
+//! comparing numbers in filter expressions, and by functions that operate on numbers.
+use std::cmp::Ordering;
+
+use serde_json::Number;
+
+/// A JSON number, which is either an integer, held exactly, or a floating point number
+#[derive(Debug, Clone, Copy)]
+pub enum Numeric {
+    /// An integer
+    Integer(i128),
+    /// A floating point number
+    Float(f64),
+}
+
+impl Numeric {
+    /// Convert to a floating point number, which may lose precision
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Numeric::Integer(i) => i as f64,
+            Numeric::Float(f) => f,
+        }
+    }
+
+    /// Add two numbers, exactly if both are integers
+    ///
+    /// If the sum of two integers overflows, the sum is computed as a floating point number.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(self, other: Self) -> Self {
+        match (self, other) {
+            (Numeric::Integer(a), Numeric::Integer(b)) => a
+                .checked_add(b)
+                .map_or_else(|| Numeric::Float(a as f64 + b as f64), Numeric::Integer),
+            (a, b) => Numeric::Float(a.as_f64() + b.as_f64()),
+        }
+    }
+
+    /// Subtract a number from this one, exactly if both are integers
+    ///
+    /// If the difference of two integers overflows, it is computed as a floating point number.
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(self, other: Self) -> Self {
+        match (self, other) {
+            (Numeric::Integer(a), Numeric::Integer(b)) => a
+                .checked_sub(b)
+                .map_or_else(|| Numeric::Float(a as f64 - b as f64), Numeric::Integer),
+            (a, b) => Numeric::Float(a.as_f64() - b.as_f64()),
+        }
+    }
+
+    /// Multiply two numbers, exactly if both are integers
+    ///
+    /// If the product of two integers overflows, it is computed as a floating point number.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(self, other: Self) -> Self {
+        match (self, other) {
+            (Numeric::Integer(a), Numeric::Integer(b)) => a
+                .checked_mul(b)
+                .map_or_else(|| Numeric::Float(a as f64 * b as f64), Numeric::Integer),
+            (a, b) => Numeric::Float(a.as_f64() * b.as_f64()),
+        }
+    }
+
+    /// Divide this number by another, which produces `None` when dividing by zero
+    ///
+    /// The quotient of two integers is an integer if the division is exact, and a floating point
+    /// number otherwise.
+    #[allow(clippy::should_implement_trait)]
+    pub fn div(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (_, Numeric::Integer(0)) => None,
+            (Numeric::Integer(a), Numeric::Integer(b)) if a % b == 0 => Some(
+                a.checked_div(b)
+                    .map_or_else(|| Numeric::Float(a as f64 / b as f64), Numeric::Integer),
+            ),
+            (_, b) if b.as_f64() == 0.0 => None,
+            (a, b) => Some(Numeric::Float(a.as_f64() / b.as_f64())),
+        }
+    }
+
+    /// The remainder of dividing this number by another, which produces `None` when dividing by
+    /// zero
+    ///
+    /// The remainder has the same sign as this number, the dividend.
+    #[allow(clippy::should_implement_trait)]
+    pub fn rem(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (_, Numeric::Integer(0)) => None,
+            (Numeric::Integer(a), Numeric::Integer(b)) => {
+                Some(Numeric::Integer(a.checked_rem(b).unwrap_or(0)))
+            }
+            (_, b) if b.as_f64() == 0.0 => None,
+            (a, b) => Some(Numeric::Float(a.as_f64() % b.as_f64())),
+        }
+    }
+
+    /// The absolute value of the number
+    pub fn abs(self) -> Self {
+        match self {
+            Numeric::Integer(i) => Numeric::Integer(i.abs()),
+            Numeric::Float(f) => Numeric::Float(f.abs()),
+        }
+    }
+
+    /// The largest integer less than or equal to the number
+    pub fn floor(self) -> Self {
+        self.map_float(f64::floor)
+    }
+
+    /// The smallest integer greater than or equal to the number
+    pub fn ceil(self) -> Self {
+        self.map_float(f64::ceil)
+    }
+
+    /// The nearest integer to the number, rounding half-way cases away from zero
+    pub fn round(self) -> Self {
+        self.map_float(f64::round)
+    }
+
+    fn map_float(self, f: impl FnOnce(f64) -> f64) -> Self {
+        match self {
+            Numeric::Integer(_) => self,
+            Numeric::Float(x) => Numeric::Float(f(x)),
+        }
+    }
+
+    /// Convert into a [`Number`], which produces `None` for floating point numbers that are not
+    /// finite
+    ///
+    /// Integers that do not fit in an `i64` or `u64` are converted to floating point numbers.
+    pub fn into_number(self) -> Option<Number> {
+        match self {
+            Numeric::Integer(i) => i64::try_from(i)
+                .map(Number::from)
+                .or_else(|_| u64::try_from(i).map(Number::from))
+                .ok()
+                .or_else(|| Number::from_f64(i as f64)),
+            Numeric::Float(f) => Number::from_f64(f),
+        }
+    }
+}
+
+impl From<&Number> for Numeric {
+    fn from(n: &Number) -> Self {
+        if let Some(i) = n.as_i64() {
+            Numeric::Integer(i.into())
+        } else if let Some(u) = n.as_u64() {
+            Numeric::Integer(u.into())
+        } else {
+            Numeric::Float(n.as_f64().unwrap_or(f64::NAN))
+        }
+    }
+}
+
+impl PartialEq for Numeric {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Numeric {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (*self, *other) {
+            (Numeric::Integer(a), Numeric::Integer(b)) => Some(a.cmp(&b)),
+            (Numeric::Integer(a), Numeric::Float(b)) => cmp_integer_float(a, b),
+            (Numeric::Float(a), Numeric::Integer(b)) => {
+                cmp_integer_float(b, a).map(Ordering::reverse)
+            }
+            (Numeric::Float(a), Numeric::Float(b)) => a.partial_cmp(&b),
+        }
+    }
+}
+
+/// Compare an integer with a floating point number, exactly if the latter is integral
+fn cmp_integer_float(a: i128, b: f64) -> Option<Ordering> {
+    /// 2<sup>127</sup>, the bounds of an `i128`
+    const BOUND: f64 = i128::MAX as f64;
+    if b.fract() == 0.0 && (-BOUND..BOUND).contains(&b) {
+        Some(a.cmp(&(b as i128)))
+    } else {
+        (a as f64).partial_cmp(&b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Number};
+
+    use super::Numeric;
+
+    fn num(value: serde_json::Value) -> Numeric {
+        match value {
+            serde_json::Value::Number(n) => Numeric::from(&n),
+            _ => panic!("not a number"),
+        }
+    }
+
+    #[test]
+    fn exact_comparison() {
+        // these are equal when compared as floating point numbers:
+        assert!(num(json!(9007199254740993_u64)) > num(json!(9007199254740992_u64)));
+        assert!(num(json!(u64::MAX)) > num(json!(u64::MAX - 1)));
+        assert!(num(json!(i64::MIN)) < num(json!(u64::MAX)));
+        assert!(num(json!(9007199254740993_u64)) > num(json!(9007199254740992.0)));
+        assert!(num(json!(1)) == num(json!(1.0)));
+        assert!(num(json!(1)) < num(json!(1.5)));
+        assert!(num(json!(-2)) < num(json!(-1.5)));
+        assert!(num(json!(2)) > num(json!(1e-300)));
+        assert!(num(json!(u64::MAX)) < num(json!(1e300)));
+    }
+
+    #[test]
+    fn exact_addition() {
+        let sum = num(json!(9007199254740993_u64)).add(num(json!(1)));
+        assert_eq!(sum.into_number(), Some(Number::from(9007199254740994_u64)));
+        let sum = num(json!(u64::MAX)).add(num(json!(u64::MAX)));
+        assert_eq!(sum.into_number(), Number::from_f64(2.0 * u64::MAX as f64));
+        let sum = num(json!(1)).add(num(json!(0.5)));
+        assert_eq!(sum.into_number(), Number::from_f64(1.5));
+        assert_eq!(
+            num(json!(i64::MIN)).abs().into_number(),
+            Some(Number::from(1_u64 << 63))
+        );
+        assert_eq!(
+            num(json!(-1.5)).round().into_number(),
+            Number::from_f64(-2.0)
+        );
+        assert_eq!(num(json!(3)).floor().into_number(), Some(Number::from(3)));
+    }
+
+    #[test]
+    fn exact_arithmetic() {
+        let diff = num(json!(9007199254740993_u64)).sub(num(json!(1)));
+        assert_eq!(diff.into_number(), Some(Number::from(9007199254740992_u64)));
+        let product = num(json!(u64::MAX)).mul(num(json!(-1)));
+        assert_eq!(product.into_number(), Number::from_f64(-(u64::MAX as f64)));
+        let quotient = num(json!(9007199254740994_u64)).div(num(json!(2)));
+        assert_eq!(
+            quotient.and_then(Numeric::into_number),
+            Some(Number::from(4503599627370497_u64))
+        );
+        let quotient = num(json!(3)).div(num(json!(2)));
+        assert_eq!(
+            quotient.and_then(Numeric::into_number),
+            Number::from_f64(1.5)
+        );
+        assert!(num(json!(1)).div(num(json!(0))).is_none());
+        assert!(num(json!(1.5)).div(num(json!(0.0))).is_none());
+        let remainder = num(json!(-7)).rem(num(json!(3)));
+        assert_eq!(
+            remainder.and_then(Numeric::into_number),
+            Some(Number::from(-1))
+        );
+        let remainder = num(json!(7.5)).rem(num(json!(2)));
+        assert_eq!(
+            remainder.and_then(Numeric::into_number),
+            Number::from_f64(1.5)
+        );
+        assert!(num(json!(1)).rem(num(json!(0))).is_none());
+    }
+}
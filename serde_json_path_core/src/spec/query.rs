@@ -1,9 +1,11 @@
 //! Types representing queries in JSONPath
+use std::cmp::Ordering;
+
 use serde_json::Value;
 
-use crate::{node::LocatedNode, path::NormalizedPath};
+use crate::{context::EvalContext, node::LocatedNode, path::NormalizedPath};
 
-use super::segment::QuerySegment;
+use super::segment::{QuerySegment, Segment};
 
 mod sealed {
     use crate::spec::{
@@ -34,7 +36,9 @@ mod sealed {
 /// A type that is query-able
 pub trait Queryable: sealed::Sealed {
     /// Query `self` using a current node, and the root node
-    fn query<'b>(&self, current: &'b Value, root: &'b Value) -> Vec<&'b Value>;
+    fn query<'b>(&self, current: &'b Value, root: &'b Value) -> Vec<&'b Value> {
+        self.query_in(current, &EvalContext::new(root))
+    }
     /// Query `self` using a current node, the root node, and the normalized path of the current
     /// node's parent
     fn query_located<'b>(
@@ -42,6 +46,18 @@ pub trait Queryable: sealed::Sealed {
         current: &'b Value,
         root: &'b Value,
         parent: NormalizedPath<'b>,
+    ) -> Vec<LocatedNode<'b>> {
+        self.query_located_in(current, &EvalContext::new(root), parent)
+    }
+    /// Query `self` using a current node, and the [`EvalContext`] of the query
+    fn query_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> Vec<&'b Value>;
+    /// Query `self` using a current node, the [`EvalContext`] of the query, and the normalized
+    /// path of the current node's parent
+    fn query_located_in<'b>(
+        &self,
+        current: &'b Value,
+        ctx: &EvalContext<'b, '_>,
+        parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>>;
 }
 
@@ -55,7 +71,11 @@ pub struct Query {
 }
 
 impl Query {
-    pub(crate) fn is_singular(&self) -> bool {
+    /// Does this query extract at most a single node
+    ///
+    /// A query is singular if it contains no descendant segments, and each of its segments is
+    /// composed of a single name or index selector.
+    pub fn is_singular(&self) -> bool {
         for s in &self.segments {
             if s.is_descendent() {
                 return false;
@@ -66,6 +86,13 @@ impl Query {
         }
         true
     }
+
+    /// Does this query contain any of the non-standard parent segments, i.e., `^`
+    pub fn has_parent_segments(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|s| matches!(s.segment, Segment::Parent))
+    }
 }
 
 impl std::fmt::Display for Query {
@@ -93,31 +120,50 @@ pub enum QueryKind {
 
 impl Queryable for Query {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Main Query", level = "trace", parent = None, ret))]
-    fn query<'b>(&self, current: &'b Value, root: &'b Value) -> Vec<&'b Value> {
+    fn query_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> Vec<&'b Value> {
+        let location = match ctx.location() {
+            // nested filters need the locations of the nodes they test:
+            Some(location) => Some(location.clone()),
+            // parent segments are only evaluated using the locations of the nodes they select
+            // from, which are known without tracking them if the query starts at the root node:
+            None if self.has_parent_segments()
+                && (matches!(self.kind, QueryKind::Root) || std::ptr::eq(current, ctx.root())) =>
+            {
+                Some(Default::default())
+            }
+            None => None,
+        };
+        if let Some(location) = location {
+            return self
+                .query_located_in(current, ctx, location)
+                .into_iter()
+                .map(|n| n.node)
+                .collect();
+        }
         let mut query = match self.kind {
-            QueryKind::Root => vec![root],
+            QueryKind::Root => vec![ctx.root()],
             QueryKind::Current => vec![current],
         };
         for segment in &self.segments {
             let mut new_query = Vec::new();
             for q in &query {
-                new_query.append(&mut segment.query(q, root));
+                new_query.append(&mut segment.query_in(q, ctx));
             }
             query = new_query;
         }
         query
     }
 
-    fn query_located<'b>(
+    fn query_located_in<'b>(
         &self,
         current: &'b Value,
-        root: &'b Value,
+        ctx: &EvalContext<'b, '_>,
         parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>> {
         let mut result: Vec<LocatedNode<'b>> = match self.kind {
             QueryKind::Root => vec![LocatedNode {
                 loc: Default::default(),
-                node: root,
+                node: ctx.root(),
             }],
             QueryKind::Current => vec![LocatedNode {
                 loc: parent,
@@ -127,7 +173,13 @@ impl Queryable for Query {
         for s in &self.segments {
             let mut r = vec![];
             for LocatedNode { loc, node } in result {
-                r.append(&mut s.query_located(node, root, loc.clone()));
+                r.append(&mut s.query_located_in(node, ctx, loc.clone()));
+            }
+            if matches!(s.segment, Segment::Parent) {
+                // siblings share a parent, which is only produced once, in document order. The
+                // locations of nodes in the same value are always ordered:
+                r.sort_by(|a, b| a.loc.partial_cmp(&b.loc).unwrap_or(Ordering::Equal));
+                r.dedup_by(|a, b| a.loc == b.loc);
             }
             result = r;
         }
@@ -1,7 +1,7 @@
 //! Types representing segments in JSONPath
 use serde_json::Value;
 
-use crate::{node::LocatedNode, path::NormalizedPath};
+use crate::{context::EvalContext, node::LocatedNode, path::NormalizedPath};
 
 use super::{query::Queryable, selector::Selector};
 
@@ -28,10 +28,12 @@ impl QuerySegment {
 
 impl std::fmt::Display for QuerySegment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if matches!(self.kind, QuerySegmentKind::Descendant) {
-            write!(f, "..")?;
+        match (&self.kind, &self.segment) {
+            (QuerySegmentKind::Descendant, Segment::DotName(name)) => write!(f, "..{name}"),
+            (QuerySegmentKind::Descendant, Segment::Wildcard) => write!(f, "..*"),
+            (QuerySegmentKind::Descendant, segment) => write!(f, "..{segment}"),
+            (QuerySegmentKind::Child, segment) => write!(f, "{segment}"),
         }
-        write!(f, "{segment}", segment = self.segment)
     }
 }
 
@@ -50,40 +52,44 @@ pub enum QuerySegmentKind {
 
 impl Queryable for QuerySegment {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Query Path Segment", level = "trace", parent = None, ret))]
-    fn query<'b>(&self, current: &'b Value, root: &'b Value) -> Vec<&'b Value> {
-        let mut query = self.segment.query(current, root);
+    fn query_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> Vec<&'b Value> {
+        let mut query = self.segment.query_in(current, ctx);
         if matches!(self.kind, QuerySegmentKind::Descendant) {
-            query.append(&mut descend(self, current, root));
+            query.append(&mut descend(self, current, ctx));
         }
         query
     }
 
-    fn query_located<'b>(
+    fn query_located_in<'b>(
         &self,
         current: &'b Value,
-        root: &'b Value,
+        ctx: &EvalContext<'b, '_>,
         parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>> {
         if matches!(self.kind, QuerySegmentKind::Descendant) {
-            let mut result = self.segment.query_located(current, root, parent.clone());
-            result.append(&mut descend_paths(self, current, root, parent));
+            let mut result = self.segment.query_located_in(current, ctx, parent.clone());
+            result.append(&mut descend_paths(self, current, ctx, parent));
             result
         } else {
-            self.segment.query_located(current, root, parent)
+            self.segment.query_located_in(current, ctx, parent)
         }
     }
 }
 
 #[cfg_attr(feature = "trace", tracing::instrument(name = "Descend", level = "trace", parent = None, ret))]
-fn descend<'b>(segment: &QuerySegment, current: &'b Value, root: &'b Value) -> Vec<&'b Value> {
+fn descend<'b>(
+    segment: &QuerySegment,
+    current: &'b Value,
+    ctx: &EvalContext<'b, '_>,
+) -> Vec<&'b Value> {
     let mut query = Vec::new();
     if let Some(list) = current.as_array() {
         for v in list {
-            query.append(&mut segment.query(v, root));
+            query.append(&mut segment.query_in(v, ctx));
         }
     } else if let Some(obj) = current.as_object() {
         for (_, v) in obj {
-            query.append(&mut segment.query(v, root));
+            query.append(&mut segment.query_in(v, ctx));
         }
     }
     query
@@ -92,17 +98,17 @@ fn descend<'b>(segment: &QuerySegment, current: &'b Value, root: &'b Value) -> V
 fn descend_paths<'b>(
     segment: &QuerySegment,
     current: &'b Value,
-    root: &'b Value,
+    ctx: &EvalContext<'b, '_>,
     parent: NormalizedPath<'b>,
 ) -> Vec<LocatedNode<'b>> {
     let mut result = Vec::new();
     if let Some(list) = current.as_array() {
         for (i, v) in list.iter().enumerate() {
-            result.append(&mut segment.query_located(v, root, parent.clone_and_push(i)));
+            result.append(&mut segment.query_located_in(v, ctx, parent.clone_and_push(i)));
         }
     } else if let Some(obj) = current.as_object() {
         for (k, v) in obj {
-            result.append(&mut segment.query_located(v, root, parent.clone_and_push(k)));
+            result.append(&mut segment.query_located_in(v, ctx, parent.clone_and_push(k)));
         }
     }
     result
@@ -117,6 +123,14 @@ pub enum Segment {
     DotName(String),
     /// The wildcard shorthand `.*`
     Wildcard,
+    /// The non-standard parent segment `^`, which selects the parent of the current node
+    ///
+    /// The parent is found using the location of the current node, so this is only evaluated
+    /// using [`Queryable::query_located_in`]. A [`Query`][crate::spec::query::Query] containing
+    /// parent segments does so when locations are being tracked, or when it starts at the root
+    /// node. Otherwise, e.g., for a relative query evaluated against a node other than the root
+    /// without tracking locations, this selects nothing.
+    Parent,
 }
 
 impl Segment {
@@ -137,7 +151,7 @@ impl Segment {
                 }
             }
             Segment::DotName(_) => true,
-            Segment::Wildcard => false,
+            Segment::Wildcard | Segment::Parent => false,
         }
     }
 
@@ -174,6 +188,7 @@ impl std::fmt::Display for Segment {
             }
             Segment::DotName(name) => write!(f, ".{name}")?,
             Segment::Wildcard => write!(f, ".*")?,
+            Segment::Parent => write!(f, "^")?,
         }
         Ok(())
     }
@@ -181,12 +196,12 @@ impl std::fmt::Display for Segment {
 
 impl Queryable for Segment {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Query Segment", level = "trace", parent = None, ret))]
-    fn query<'b>(&self, current: &'b Value, root: &'b Value) -> Vec<&'b Value> {
+    fn query_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> Vec<&'b Value> {
         let mut query = Vec::new();
         match self {
             Segment::LongHand(selectors) => {
                 for selector in selectors {
-                    query.append(&mut selector.query(current, root));
+                    query.append(&mut selector.query_in(current, ctx));
                 }
             }
             Segment::DotName(key) => {
@@ -207,21 +222,24 @@ impl Queryable for Segment {
                     }
                 }
             }
+            // the parent can not be found without the location of the current node, so this is
+            // only evaluated by `query_located_in`:
+            Segment::Parent => (),
         }
         query
     }
 
-    fn query_located<'b>(
+    fn query_located_in<'b>(
         &self,
         current: &'b Value,
-        root: &'b Value,
+        ctx: &EvalContext<'b, '_>,
         mut parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>> {
         let mut result = vec![];
         match self {
             Segment::LongHand(selectors) => {
                 for s in selectors {
-                    result.append(&mut s.query_located(current, root, parent.clone()));
+                    result.append(&mut s.query_located_in(current, ctx, parent.clone()));
                 }
             }
             Segment::DotName(name) => {
@@ -250,6 +268,13 @@ impl Queryable for Segment {
                     }
                 }
             }
+            Segment::Parent => {
+                if let Some(loc) = parent.parent() {
+                    if let Some(node) = loc.resolve(ctx.root()) {
+                        result.push(LocatedNode { loc, node });
+                    }
+                }
+            }
         }
         result
     }
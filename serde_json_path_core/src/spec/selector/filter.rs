@@ -1,11 +1,15 @@
 //! Types representing filter selectors in JSONPath
+use std::collections::HashSet;
+
 use serde_json::{Number, Value};
 
 use crate::{
+    context::EvalContext,
     node::LocatedNode,
-    path::NormalizedPath,
+    path::{NormalizedPath, PathElement},
     spec::{
         functions::{FunctionExpr, JsonPathValue, Validated},
+        number::Numeric,
         query::{Query, QueryKind, Queryable},
         segment::{QuerySegment, Segment},
     },
@@ -18,7 +22,9 @@ mod sealed {
 
     use crate::spec::functions::FunctionExpr;
 
-    use super::{BasicExpr, ComparisonExpr, ExistExpr, LogicalAndExpr, LogicalOrExpr};
+    use super::{
+        BasicExpr, ComparisonExpr, ExistExpr, LogicalAndExpr, LogicalOrExpr, MembershipExpr,
+    };
 
     pub trait Sealed {}
     impl Sealed for Value {}
@@ -27,17 +33,22 @@ mod sealed {
     impl Sealed for BasicExpr {}
     impl Sealed for ExistExpr {}
     impl Sealed for ComparisonExpr {}
+    impl Sealed for MembershipExpr {}
     impl<V> Sealed for FunctionExpr<V> {}
 }
 
 /// Trait for testing a filter type
 pub trait TestFilter: sealed::Sealed {
     /// Test self using the current and root nodes
-    fn test_filter<'b>(&self, current: &'b Value, root: &'b Value) -> bool;
+    fn test_filter<'b>(&self, current: &'b Value, root: &'b Value) -> bool {
+        self.test_filter_in(current, &EvalContext::new(root))
+    }
+    /// Test self using the current node, and the [`EvalContext`] of the query
+    fn test_filter_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> bool;
 }
 
 impl TestFilter for Value {
-    fn test_filter<'b>(&self, _current: &'b Value, _root: &'b Value) -> bool {
+    fn test_filter_in<'b>(&self, _current: &'b Value, _ctx: &EvalContext<'b, '_>) -> bool {
         match self {
             Value::Null => false,
             Value::Bool(b) => *b,
@@ -57,33 +68,49 @@ impl std::fmt::Display for Filter {
     }
 }
 
+impl Filter {
+    /// Test a child of the current node, making its location available to the filter if
+    /// locations are being tracked
+    fn test_child<'b>(
+        &self,
+        child: &'b Value,
+        ctx: &EvalContext<'b, '_>,
+        location: impl FnOnce() -> NormalizedPath<'b>,
+    ) -> bool {
+        match ctx.at(location) {
+            Some(child_ctx) => self.0.test_filter_in(child, &child_ctx),
+            None => self.0.test_filter_in(child, ctx),
+        }
+    }
+}
+
 impl Queryable for Filter {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Query Filter", level = "trace", parent = None, ret))]
-    fn query<'b>(&self, current: &'b Value, root: &'b Value) -> Vec<&'b Value> {
+    fn query_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> Vec<&'b Value> {
         if let Some(list) = current.as_array() {
             list.iter()
-                .filter(|v| self.0.test_filter(v, root))
+                .filter(|v| self.0.test_filter_in(v, ctx))
                 .collect()
         } else if let Some(obj) = current.as_object() {
             obj.iter()
                 .map(|(_, v)| v)
-                .filter(|v| self.0.test_filter(v, root))
+                .filter(|v| self.0.test_filter_in(v, ctx))
                 .collect()
         } else {
             vec![]
         }
     }
 
-    fn query_located<'b>(
+    fn query_located_in<'b>(
         &self,
         current: &'b Value,
-        root: &'b Value,
+        ctx: &EvalContext<'b, '_>,
         parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>> {
         if let Some(list) = current.as_array() {
             list.iter()
                 .enumerate()
-                .filter(|(_, v)| self.0.test_filter(v, root))
+                .filter(|(i, v)| self.test_child(v, ctx, || parent.clone_and_push(*i)))
                 .map(|(i, v)| LocatedNode {
                     loc: parent.clone_and_push(i),
                     node: v,
@@ -91,7 +118,7 @@ impl Queryable for Filter {
                 .collect()
         } else if let Some(obj) = current.as_object() {
             obj.iter()
-                .filter(|(_, v)| self.0.test_filter(v, root))
+                .filter(|(k, v)| self.test_child(v, ctx, || parent.clone_and_push(*k)))
                 .map(|(k, v)| LocatedNode {
                     loc: parent.clone_and_push(k),
                     node: v,
@@ -125,8 +152,8 @@ impl std::fmt::Display for LogicalOrExpr {
 
 impl TestFilter for LogicalOrExpr {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Test Logical Or Expr", level = "trace", parent = None, ret))]
-    fn test_filter<'b>(&self, current: &'b Value, root: &'b Value) -> bool {
-        self.0.iter().any(|expr| expr.test_filter(current, root))
+    fn test_filter_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> bool {
+        self.0.iter().any(|expr| expr.test_filter_in(current, ctx))
     }
 }
 
@@ -149,8 +176,8 @@ impl std::fmt::Display for LogicalAndExpr {
 
 impl TestFilter for LogicalAndExpr {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Test Logical And Expr", level = "trace", parent = None, ret))]
-    fn test_filter<'b>(&self, current: &'b Value, root: &'b Value) -> bool {
-        self.0.iter().all(|expr| expr.test_filter(current, root))
+    fn test_filter_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> bool {
+        self.0.iter().all(|expr| expr.test_filter_in(current, ctx))
     }
 }
 
@@ -171,6 +198,11 @@ pub enum BasicExpr {
     FuncExpr(FunctionExpr<Validated>),
     /// The inverse of a function expression, i.e., preceded by `!`
     NotFuncExpr(FunctionExpr<Validated>),
+    /// A membership expression, which tests if a JSON value is in an array
+    ///
+    /// This is not part of RFC 9535, and is only produced by the parser when the `membership`
+    /// feature of `serde_json_path` is enabled.
+    Membership(MembershipExpr),
 }
 
 impl std::fmt::Display for BasicExpr {
@@ -183,6 +215,7 @@ impl std::fmt::Display for BasicExpr {
             BasicExpr::NotExist(exist) => write!(f, "!{exist}"),
             BasicExpr::FuncExpr(expr) => write!(f, "{expr}"),
             BasicExpr::NotFuncExpr(expr) => write!(f, "{expr}"),
+            BasicExpr::Membership(expr) => write!(f, "{expr}"),
         }
     }
 }
@@ -199,15 +232,16 @@ impl BasicExpr {
 
 impl TestFilter for BasicExpr {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Test Basic Expr", level = "trace", parent = None, ret))]
-    fn test_filter<'b>(&self, current: &'b Value, root: &'b Value) -> bool {
+    fn test_filter_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> bool {
         match self {
-            BasicExpr::Paren(expr) => expr.test_filter(current, root),
-            BasicExpr::NotParen(expr) => !expr.test_filter(current, root),
-            BasicExpr::Relation(expr) => expr.test_filter(current, root),
-            BasicExpr::Exist(expr) => expr.test_filter(current, root),
-            BasicExpr::NotExist(expr) => !expr.test_filter(current, root),
-            BasicExpr::FuncExpr(expr) => expr.test_filter(current, root),
-            BasicExpr::NotFuncExpr(expr) => !expr.test_filter(current, root),
+            BasicExpr::Paren(expr) => expr.test_filter_in(current, ctx),
+            BasicExpr::NotParen(expr) => !expr.test_filter_in(current, ctx),
+            BasicExpr::Relation(expr) => expr.test_filter_in(current, ctx),
+            BasicExpr::Exist(expr) => expr.test_filter_in(current, ctx),
+            BasicExpr::NotExist(expr) => !expr.test_filter_in(current, ctx),
+            BasicExpr::FuncExpr(expr) => expr.test_filter_in(current, ctx),
+            BasicExpr::NotFuncExpr(expr) => !expr.test_filter_in(current, ctx),
+            BasicExpr::Membership(expr) => expr.test_filter_in(current, ctx),
         }
     }
 }
@@ -228,8 +262,8 @@ impl std::fmt::Display for ExistExpr {
 
 impl TestFilter for ExistExpr {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Test Exists Expr", level = "trace", parent = None, ret))]
-    fn test_filter<'b>(&self, current: &'b Value, root: &'b Value) -> bool {
-        !self.0.query(current, root).is_empty()
+    fn test_filter_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> bool {
+        !self.0.query_in(current, ctx).is_empty()
     }
 }
 
@@ -263,15 +297,7 @@ fn value_equal_to(left: &Value, right: &Value) -> bool {
 }
 
 fn number_equal_to(left: &Number, right: &Number) -> bool {
-    if let (Some(l), Some(r)) = (left.as_f64(), right.as_f64()) {
-        l == r
-    } else if let (Some(l), Some(r)) = (left.as_i64(), right.as_i64()) {
-        l == r
-    } else if let (Some(l), Some(r)) = (left.as_u64(), right.as_u64()) {
-        l == r
-    } else {
-        false
-    }
+    Numeric::from(left) == Numeric::from(right)
 }
 
 fn value_less_than(left: &Value, right: &Value) -> bool {
@@ -325,9 +351,9 @@ impl std::fmt::Display for ComparisonExpr {
 
 impl TestFilter for ComparisonExpr {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Test Comparison Expr", level = "trace", parent = None, ret))]
-    fn test_filter<'b>(&self, current: &'b Value, root: &'b Value) -> bool {
-        let left = self.left.as_value(current, root);
-        let right = self.right.as_value(current, root);
+    fn test_filter_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> bool {
+        let left = self.left.as_value_in(current, ctx);
+        let right = self.right.as_value_in(current, ctx);
         match self.op {
             ComparisonOperator::EqualTo => check_equal_to(&left, &right),
             ComparisonOperator::NotEqualTo => !check_equal_to(&left, &right),
@@ -351,15 +377,7 @@ impl TestFilter for ComparisonExpr {
 }
 
 fn number_less_than(n1: &Number, n2: &Number) -> bool {
-    if let (Some(a), Some(b)) = (n1.as_f64(), n2.as_f64()) {
-        a < b
-    } else if let (Some(a), Some(b)) = (n1.as_i64(), n2.as_i64()) {
-        a < b
-    } else if let (Some(a), Some(b)) = (n1.as_u64(), n2.as_u64()) {
-        a < b
-    } else {
-        false
-    }
+    Numeric::from(n1) < Numeric::from(n2)
 }
 
 /// The comparison operator
@@ -392,6 +410,117 @@ impl std::fmt::Display for ComparisonOperator {
     }
 }
 
+/// A membership expression, e.g., `@.status in ['open', 'pending']` or `@.tags contains 'x'`
+///
+/// The expression is true if the value is equal to any element of the array, in the same way as
+/// the `==` comparison. A `nin` expression is the inverse of the equivalent `in` expression.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MembershipExpr {
+    /// The JSON value to look for
+    pub value: Comparable,
+    /// The membership operator
+    pub op: MembershipOperator,
+    /// The array in which to look for the value
+    pub array: ArrayOperand,
+}
+
+impl std::fmt::Display for MembershipExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.op {
+            MembershipOperator::In | MembershipOperator::NotIn => write!(
+                f,
+                "{value} {op} {array}",
+                value = self.value,
+                op = self.op,
+                array = self.array
+            ),
+            MembershipOperator::Contains => write!(
+                f,
+                "{array} {op} {value}",
+                array = self.array,
+                op = self.op,
+                value = self.value
+            ),
+        }
+    }
+}
+
+impl TestFilter for MembershipExpr {
+    #[cfg_attr(feature = "trace", tracing::instrument(name = "Test Membership Expr", level = "trace", parent = None, ret))]
+    fn test_filter_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> bool {
+        let value = self.value.as_value_in(current, ctx);
+        let found = match &value {
+            JsonPathValue::Node(v) => self.array.contains_in(v, current, ctx),
+            JsonPathValue::Value(v) => self.array.contains_in(v, current, ctx),
+            _ => false,
+        };
+        match self.op {
+            MembershipOperator::In | MembershipOperator::Contains => found,
+            MembershipOperator::NotIn => !found,
+        }
+    }
+}
+
+/// The membership operator
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MembershipOperator {
+    /// `in`, with the value on the left and the array on the right
+    In,
+    /// `nin`, with the value on the left and the array on the right
+    NotIn,
+    /// `contains`, with the array on the left and the value on the right
+    Contains,
+}
+
+impl std::fmt::Display for MembershipOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MembershipOperator::In => write!(f, "in"),
+            MembershipOperator::NotIn => write!(f, "nin"),
+            MembershipOperator::Contains => write!(f, "contains"),
+        }
+    }
+}
+
+/// The array operand of a [`MembershipExpr`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ArrayOperand {
+    /// A list of literals, e.g., `['open', 'pending']`
+    List(LiteralList),
+    /// A comparable that produces a JSON array, e.g., `@.tags`
+    ///
+    /// A comparable that does not produce an array contains no values.
+    Comparable(Comparable),
+}
+
+impl ArrayOperand {
+    fn contains_in<'b>(
+        &self,
+        value: &Value,
+        current: &'b Value,
+        ctx: &EvalContext<'b, '_>,
+    ) -> bool {
+        match self {
+            ArrayOperand::List(list) => list.contains(value),
+            ArrayOperand::Comparable(c) => match &c.as_value_in(current, ctx) {
+                JsonPathValue::Node(Value::Array(a)) | JsonPathValue::Value(Value::Array(a)) => {
+                    a.iter().any(|v| value_equal_to(v, value))
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ArrayOperand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArrayOperand::List(list) => write!(f, "{list}"),
+            ArrayOperand::Comparable(c) => write!(f, "{c}"),
+        }
+    }
+}
+
 /// A type that is comparable
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Comparable {
@@ -403,6 +532,11 @@ pub enum Comparable {
     SingularQuery(SingularQuery),
     /// A function expression that can only produce a `ValueType`
     FunctionExpr(FunctionExpr<Validated>),
+    /// An arithmetic expression over two comparables
+    ///
+    /// This is not part of RFC 9535, and is only produced by the parser when the `arithmetic`
+    /// feature of `serde_json_path` is enabled.
+    Arithmetic(Box<ArithmeticExpr>),
 }
 
 impl std::fmt::Display for Comparable {
@@ -411,25 +545,36 @@ impl std::fmt::Display for Comparable {
             Comparable::Literal(lit) => write!(f, "{lit}"),
             Comparable::SingularQuery(path) => write!(f, "{path}"),
             Comparable::FunctionExpr(expr) => write!(f, "{expr}"),
+            Comparable::Arithmetic(expr) => write!(f, "{expr}"),
         }
     }
 }
 
 impl Comparable {
     #[doc(hidden)]
-    #[cfg_attr(feature = "trace", tracing::instrument(name = "Comparable::as_value", level = "trace", parent = None, ret))]
     pub fn as_value<'a, 'b: 'a>(
         &'a self,
         current: &'b Value,
         root: &'b Value,
+    ) -> JsonPathValue<'a> {
+        self.as_value_in(current, &EvalContext::new(root))
+    }
+
+    #[doc(hidden)]
+    #[cfg_attr(feature = "trace", tracing::instrument(name = "Comparable::as_value", level = "trace", parent = None, ret))]
+    pub fn as_value_in<'a, 'b: 'a>(
+        &'a self,
+        current: &'b Value,
+        ctx: &EvalContext<'b, 'a>,
     ) -> JsonPathValue<'a> {
         match self {
             Comparable::Literal(lit) => lit.into(),
-            Comparable::SingularQuery(sp) => match sp.eval_query(current, root) {
+            Comparable::SingularQuery(sp) => match sp.eval_query_in(current, ctx) {
                 Some(v) => JsonPathValue::Node(v),
                 None => JsonPathValue::Nothing,
             },
-            Comparable::FunctionExpr(expr) => expr.evaluate(current, root),
+            Comparable::FunctionExpr(expr) => expr.evaluate_in(current, ctx),
+            Comparable::Arithmetic(expr) => expr.evaluate_in(current, ctx),
         }
     }
 
@@ -442,6 +587,116 @@ impl Comparable {
     }
 }
 
+/// An arithmetic expression, e.g., `@.price * @.quantity`
+///
+/// Both operands must produce numbers, otherwise the expression produces nothing, as does
+/// division, or taking the remainder, by zero.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ArithmeticExpr {
+    /// The operand on the left of the operator
+    pub left: Comparable,
+    /// The arithmetic operator
+    pub op: ArithmeticOperator,
+    /// The operand on the right of the operator
+    pub right: Comparable,
+}
+
+impl ArithmeticExpr {
+    #[cfg_attr(feature = "trace", tracing::instrument(name = "Evaluate Arithmetic Expr", level = "trace", parent = None, ret))]
+    fn evaluate_in<'a, 'b: 'a>(
+        &'a self,
+        current: &'b Value,
+        ctx: &EvalContext<'b, 'a>,
+    ) -> JsonPathValue<'a> {
+        let left = self.left.as_value_in(current, ctx);
+        let right = self.right.as_value_in(current, ctx);
+        let (Some(l), Some(r)) = (as_numeric(&left), as_numeric(&right)) else {
+            return JsonPathValue::Nothing;
+        };
+        let result = match self.op {
+            ArithmeticOperator::Add => Some(l.add(r)),
+            ArithmeticOperator::Subtract => Some(l.sub(r)),
+            ArithmeticOperator::Multiply => Some(l.mul(r)),
+            ArithmeticOperator::Divide => l.div(r),
+            ArithmeticOperator::Remainder => l.rem(r),
+        };
+        result
+            .and_then(Numeric::into_number)
+            .map_or(JsonPathValue::Nothing, |n| {
+                JsonPathValue::Value(Value::Number(n))
+            })
+    }
+}
+
+fn as_numeric(value: &JsonPathValue) -> Option<Numeric> {
+    match value {
+        JsonPathValue::Node(Value::Number(n)) | JsonPathValue::Value(Value::Number(n)) => {
+            Some(Numeric::from(n))
+        }
+        _ => None,
+    }
+}
+
+impl std::fmt::Display for ArithmeticExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precedence = self.op.precedence();
+        // operators are left-associative, so an operand on the left is grouped if its operator
+        // binds more loosely than this one, and an operand on the right is also grouped if its
+        // operator binds equally tightly:
+        match &self.left {
+            Comparable::Arithmetic(left) if left.op.precedence() < precedence => {
+                write!(f, "({left})")?;
+            }
+            left => write!(f, "{left}")?,
+        }
+        write!(f, "{op}", op = self.op)?;
+        match &self.right {
+            Comparable::Arithmetic(right) if right.op.precedence() <= precedence => {
+                write!(f, "({right})")
+            }
+            right => write!(f, "{right}"),
+        }
+    }
+}
+
+/// The arithmetic operator
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArithmeticOperator {
+    /// `+`
+    Add,
+    /// `-`
+    Subtract,
+    /// `*`
+    Multiply,
+    /// `/`
+    Divide,
+    /// `%`
+    Remainder,
+}
+
+impl ArithmeticOperator {
+    fn precedence(self) -> u8 {
+        match self {
+            ArithmeticOperator::Add | ArithmeticOperator::Subtract => 1,
+            ArithmeticOperator::Multiply
+            | ArithmeticOperator::Divide
+            | ArithmeticOperator::Remainder => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for ArithmeticOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArithmeticOperator::Add => write!(f, "+"),
+            ArithmeticOperator::Subtract => write!(f, "-"),
+            ArithmeticOperator::Multiply => write!(f, "*"),
+            ArithmeticOperator::Divide => write!(f, "/"),
+            ArithmeticOperator::Remainder => write!(f, "%"),
+        }
+    }
+}
+
 /// A literal JSON value that can be represented in a JSONPath query
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Literal {
@@ -472,13 +727,111 @@ impl std::fmt::Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Literal::Number(n) => write!(f, "{n}"),
-            Literal::String(s) => write!(f, "'{s}'"),
+            // strings are escaped in the same way as names, so that the output is valid JSONPath:
+            Literal::String(s) => write!(f, "'{s}'", s = PathElement::from(s.as_str())),
             Literal::Bool(b) => write!(f, "{b}"),
             Literal::Null => write!(f, "null"),
         }
     }
 }
 
+/// A list of literal JSON values, e.g., `['open', 'pending']`
+///
+/// The literals are hashed when the list is constructed, so that testing if the list contains a
+/// JSON value does not depend on the length of the list.
+#[derive(Debug, Clone)]
+pub struct LiteralList {
+    literals: Vec<Literal>,
+    keys: HashSet<LiteralKey>,
+    /// Strings are held separately, so that they can be looked up without being copied
+    strings: HashSet<String>,
+}
+
+impl LiteralList {
+    /// Create a list from its literals
+    pub fn new(literals: Vec<Literal>) -> Self {
+        let mut keys = HashSet::new();
+        let mut strings = HashSet::new();
+        for lit in &literals {
+            match lit {
+                Literal::String(s) => strings.insert(s.to_owned()),
+                Literal::Number(n) => keys.insert(LiteralKey::from_number(n)),
+                Literal::Bool(b) => keys.insert(LiteralKey::Bool(*b)),
+                Literal::Null => keys.insert(LiteralKey::Null),
+            };
+        }
+        Self {
+            literals,
+            keys,
+            strings,
+        }
+    }
+
+    /// The literals in the list, in the order they were given
+    pub fn literals(&self) -> &[Literal] {
+        &self.literals
+    }
+
+    /// Check if the list contains a JSON value
+    ///
+    /// Numbers are compared exactly, so that, e.g., `1` and `1.0` are equal.
+    pub fn contains(&self, value: &Value) -> bool {
+        match value {
+            Value::Null => self.keys.contains(&LiteralKey::Null),
+            Value::Bool(b) => self.keys.contains(&LiteralKey::Bool(*b)),
+            Value::Number(n) => self.keys.contains(&LiteralKey::from_number(n)),
+            Value::String(s) => self.strings.contains(s.as_str()),
+            Value::Array(_) | Value::Object(_) => false,
+        }
+    }
+}
+
+impl PartialEq for LiteralList {
+    fn eq(&self, other: &Self) -> bool {
+        self.literals == other.literals
+    }
+}
+
+impl Eq for LiteralList {}
+
+impl std::fmt::Display for LiteralList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (i, lit) in self.literals.iter().enumerate() {
+            write!(f, "{lit}")?;
+            if i != self.literals.len() - 1 {
+                write!(f, ",")?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+/// The hashed form of a [`Literal`] that is not a string
+///
+/// Integral numbers are held as integers, so that keys are equal when their numbers are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum LiteralKey {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    /// The bits of a floating point number that is not integral
+    Float(u64),
+}
+
+impl LiteralKey {
+    fn from_number(n: &Number) -> Self {
+        match Numeric::from(n) {
+            Numeric::Integer(i) => Self::Integer(i),
+            // the conversion saturates, so only integral numbers in range compare as equal:
+            Numeric::Float(f) if Numeric::Integer(f as i128) == Numeric::Float(f) => {
+                Self::Integer(f as i128)
+            }
+            Numeric::Float(f) => Self::Float(f.to_bits()),
+        }
+    }
+}
+
 /// A segment in a singular query
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum SingularQuerySegment {
@@ -516,6 +869,7 @@ impl TryFrom<QuerySegment> for SingularQuerySegment {
             }
             Segment::DotName(name) => Ok(Self::Name(Name(name))),
             Segment::Wildcard => Err(NonSingularQueryError::Wildcard),
+            Segment::Parent => Err(NonSingularQueryError::Parent),
         }
     }
 }
@@ -545,11 +899,26 @@ pub struct SingularQuery {
 
 impl SingularQuery {
     /// Evaluate the singular query
-    #[cfg_attr(feature = "trace", tracing::instrument(name = "SingularQuery::eval_query", level = "trace", parent = None, ret))]
     pub fn eval_query<'b>(&self, current: &'b Value, root: &'b Value) -> Option<&'b Value> {
-        let mut target = match self.kind {
-            SingularQueryKind::Absolute => root,
+        self.eval_query_in(current, &EvalContext::new(root))
+    }
+
+    /// Evaluate the singular query using the [`EvalContext`] of the query
+    ///
+    /// The produced node may be within a variable of the [`Context`], so it lives no longer than
+    /// either the queried value or the [`Context`].
+    ///
+    /// [`Context`]: crate::context::Context
+    #[cfg_attr(feature = "trace", tracing::instrument(name = "SingularQuery::eval_query_in", level = "trace", parent = None, ret))]
+    pub fn eval_query_in<'a>(
+        &self,
+        current: &'a Value,
+        ctx: &EvalContext<'a, 'a>,
+    ) -> Option<&'a Value> {
+        let mut target = match &self.kind {
+            SingularQueryKind::Absolute => ctx.root(),
             SingularQueryKind::Relative => current,
+            SingularQueryKind::Variable(name) => ctx.var(name)?,
         };
         for segment in &self.segments {
             match segment {
@@ -592,9 +961,10 @@ impl TryFrom<Query> for SingularQuery {
 
 impl std::fmt::Display for SingularQuery {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.kind {
+        match &self.kind {
             SingularQueryKind::Absolute => write!(f, "$")?,
             SingularQueryKind::Relative => write!(f, "@")?,
+            SingularQueryKind::Variable(name) => write!(f, "%{name}")?,
         }
         for s in &self.segments {
             write!(f, "[{s}]")?;
@@ -604,12 +974,16 @@ impl std::fmt::Display for SingularQuery {
 }
 
 /// The kind of singular query
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum SingularQueryKind {
     /// Referencing the root node, i.e., `$`
     Absolute,
     /// Referencing the current node, i.e., `@`
     Relative,
+    /// Referencing a variable bound in the [`Context`][crate::context::Context], i.e., `%name`
+    ///
+    /// This is not part of the JSONPath standard.
+    Variable(String),
 }
 
 impl From<QueryKind> for SingularQueryKind {
@@ -642,4 +1016,8 @@ pub enum NonSingularQueryError {
     /// A filter segment
     #[error("filter segments are not singular")]
     Filter,
+    /// A parent segment, which is not supported in singular queries, since it needs the location
+    /// of the node that it selects from
+    #[error("parent segments can not be used in singular queries, e.g., in comparisons")]
+    Parent,
 }
@@ -2,6 +2,7 @@
 use serde_json::Value;
 
 use crate::{
+    context::EvalContext,
     node::LocatedNode,
     path::NormalizedPath,
     spec::{integer::Integer, query::Queryable},
@@ -21,7 +22,7 @@ impl std::fmt::Display for Index {
 
 impl Queryable for Index {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Query Index", level = "trace", parent = None, ret))]
-    fn query<'b>(&self, current: &'b Value, _root: &'b Value) -> Vec<&'b Value> {
+    fn query_in<'b>(&self, current: &'b Value, _ctx: &EvalContext<'b, '_>) -> Vec<&'b Value> {
         if let Some(list) = current.as_array() {
             if self.0 < 0 {
                 let abs = self.0.abs();
@@ -43,10 +44,10 @@ impl Queryable for Index {
         }
     }
 
-    fn query_located<'b>(
+    fn query_located_in<'b>(
         &self,
         current: &'b Value,
-        _root: &'b Value,
+        _ctx: &EvalContext<'b, '_>,
         mut parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>> {
         if let Some((index, node)) = current.as_array().and_then(|list| {
@@ -6,7 +6,7 @@ pub mod slice;
 
 use serde_json::Value;
 
-use crate::{node::LocatedNode, path::NormalizedPath};
+use crate::{context::EvalContext, node::LocatedNode, path::NormalizedPath};
 
 use self::{filter::Filter, index::Index, name::Name, slice::Slice};
 
@@ -51,10 +51,10 @@ impl std::fmt::Display for Selector {
 
 impl Queryable for Selector {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Query Selector", level = "trace", parent = None, ret))]
-    fn query<'b>(&self, current: &'b Value, root: &'b Value) -> Vec<&'b Value> {
+    fn query_in<'b>(&self, current: &'b Value, ctx: &EvalContext<'b, '_>) -> Vec<&'b Value> {
         let mut query = Vec::new();
         match self {
-            Selector::Name(name) => query.append(&mut name.query(current, root)),
+            Selector::Name(name) => query.append(&mut name.query_in(current, ctx)),
             Selector::Wildcard => {
                 if let Some(list) = current.as_array() {
                     for v in list {
@@ -66,21 +66,21 @@ impl Queryable for Selector {
                     }
                 }
             }
-            Selector::Index(index) => query.append(&mut index.query(current, root)),
-            Selector::ArraySlice(slice) => query.append(&mut slice.query(current, root)),
-            Selector::Filter(filter) => query.append(&mut filter.query(current, root)),
+            Selector::Index(index) => query.append(&mut index.query_in(current, ctx)),
+            Selector::ArraySlice(slice) => query.append(&mut slice.query_in(current, ctx)),
+            Selector::Filter(filter) => query.append(&mut filter.query_in(current, ctx)),
         }
         query
     }
 
-    fn query_located<'b>(
+    fn query_located_in<'b>(
         &self,
         current: &'b Value,
-        root: &'b Value,
+        ctx: &EvalContext<'b, '_>,
         parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>> {
         match self {
-            Selector::Name(name) => name.query_located(current, root, parent),
+            Selector::Name(name) => name.query_located_in(current, ctx, parent),
             Selector::Wildcard => {
                 if let Some(list) = current.as_array() {
                     list.iter()
@@ -101,9 +101,9 @@ impl Queryable for Selector {
                     vec![]
                 }
             }
-            Selector::Index(index) => index.query_located(current, root, parent),
-            Selector::ArraySlice(slice) => slice.query_located(current, root, parent),
-            Selector::Filter(filter) => filter.query_located(current, root, parent),
+            Selector::Index(index) => index.query_located_in(current, ctx, parent),
+            Selector::ArraySlice(slice) => slice.query_located_in(current, ctx, parent),
+            Selector::Filter(filter) => filter.query_located_in(current, ctx, parent),
         }
     }
 }
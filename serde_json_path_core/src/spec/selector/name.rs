@@ -1,7 +1,12 @@
 //! Name selector for selecting object keys in JSONPath
 use serde_json::Value;
 
-use crate::{node::LocatedNode, path::NormalizedPath, spec::query::Queryable};
+use crate::{
+    context::EvalContext,
+    node::LocatedNode,
+    path::{NormalizedPath, PathElement},
+    spec::query::Queryable,
+};
 
 /// Select a single JSON object key
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -16,13 +21,15 @@ impl Name {
 
 impl std::fmt::Display for Name {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "'{name}'", name = self.0)
+        // names are escaped in the same way as in a normalized path, so that the output is valid
+        // JSONPath:
+        write!(f, "'{name}'", name = PathElement::from(self.as_str()))
     }
 }
 
 impl Queryable for Name {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Query Name", level = "trace", parent = None, ret))]
-    fn query<'b>(&self, current: &'b Value, _root: &'b Value) -> Vec<&'b Value> {
+    fn query_in<'b>(&self, current: &'b Value, _ctx: &EvalContext<'b, '_>) -> Vec<&'b Value> {
         if let Some(obj) = current.as_object() {
             obj.get(&self.0).into_iter().collect()
         } else {
@@ -30,10 +37,10 @@ impl Queryable for Name {
         }
     }
 
-    fn query_located<'b>(
+    fn query_located_in<'b>(
         &self,
         current: &'b Value,
-        _root: &'b Value,
+        _ctx: &EvalContext<'b, '_>,
         mut parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>> {
         if let Some((name, node)) = current.as_object().and_then(|o| o.get_key_value(&self.0)) {
@@ -2,6 +2,7 @@
 use serde_json::Value;
 
 use crate::{
+    context::EvalContext,
     node::LocatedNode,
     path::NormalizedPath,
     spec::{integer::Integer, query::Queryable},
@@ -123,7 +124,7 @@ impl Slice {
 
 impl Queryable for Slice {
     #[cfg_attr(feature = "trace", tracing::instrument(name = "Query Slice", level = "trace", parent = None, ret))]
-    fn query<'b>(&self, current: &'b Value, _root: &'b Value) -> Vec<&'b Value> {
+    fn query_in<'b>(&self, current: &'b Value, _ctx: &EvalContext<'b, '_>) -> Vec<&'b Value> {
         if let Some(list) = current.as_array() {
             let mut query = Vec::new();
             let step = self.step.unwrap_or(Integer::from_i64_unchecked(1));
@@ -168,10 +169,10 @@ impl Queryable for Slice {
         }
     }
 
-    fn query_located<'b>(
+    fn query_located_in<'b>(
         &self,
         current: &'b Value,
-        _root: &'b Value,
+        _ctx: &EvalContext<'b, '_>,
         parent: NormalizedPath<'b>,
     ) -> Vec<LocatedNode<'b>> {
         if let Some(list) = current.as_array() {
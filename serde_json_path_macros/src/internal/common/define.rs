@@ -2,7 +2,7 @@ use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use syn::{ItemFn, LitStr};
 
-use crate::common::extract::FnArgument;
+use crate::common::extract::{ArgConversion, Arity, FnArgument, ReturnConversion};
 
 use super::extract::{extract_components, Components};
 
@@ -14,6 +14,8 @@ pub(crate) struct Expanded {
     pub(crate) evaluator_name: Ident,
     pub(crate) result: TokenStream,
     pub(crate) core: TokenStream,
+    /// Whether the function accepts a `&FunctionContext`
+    pub(crate) uses_context: bool,
 }
 
 /// Expand the macro input to produce the common elements used in the `#[function]` and
@@ -33,6 +35,10 @@ pub(crate) fn expand(input: ItemFn, name_str: Option<LitStr>) -> Result<Expanded
         evaluator_name,
         result,
         args,
+        context,
+        params,
+        fallible,
+        output,
         ret,
         inputs,
     } = match extract_components(sig) {
@@ -41,8 +47,10 @@ pub(crate) fn expand(input: ItemFn, name_str: Option<LitStr>) -> Result<Expanded
     };
     // Stringified name of the function:
     let name_str = name_str.unwrap_or_else(|| LitStr::new(name.to_string().as_str(), name.span()));
-    // The number of arguments the function accepts:
-    let args_len = args.len();
+    // The number of arguments the function accepts, where `max_args` is `None` if the function
+    // accepts any number of arguments:
+    let min_args = args.iter().filter(|a| a.arity == Arity::Required).count();
+    let max_args = (!args.iter().any(|a| a.arity == Arity::Variadic)).then_some(args.len());
     // Generate token streams for some needed types:
     let lazy = quote! {
         std::sync::LazyLock
@@ -55,33 +63,81 @@ pub(crate) fn expand(input: ItemFn, name_str: Option<LitStr>) -> Result<Expanded
     };
     // Generate code for checking each individual argument in a query at parse time:
     let arg_checks = args.iter().enumerate().map(|(idx, arg)| {
-        let FnArgument { ident: _, ty } = arg;
-        quote! {
-            match a[#idx].as_type_kind() {
+        let FnArgument {
+            ident: _,
+            ty,
+            conversion: _,
+            arity,
+        } = arg;
+        let check = quote! {
+            match arg.as_type_kind() {
                 #res::Ok(tk) => {
                     if !tk.converts_to(#ty::json_path_type()) {
                         return #res::Err(#core::FunctionValidationError::MismatchTypeKind {
                             name: String::from(#name_str),
                             expected: #ty::json_path_type(),
                             received: tk,
-                            position: #idx,
+                            position,
                         });
                     }
                 },
                 #res::Err(err) => return #res::Err(err)
             }
+        };
+        match arity {
+            Arity::Required | Arity::Optional => quote! {
+                if let std::option::Option::Some(arg) = a.get(#idx) {
+                    let position = #idx;
+                    #check
+                }
+            },
+            // every remaining argument is checked against the element type:
+            Arity::Variadic => quote! {
+                for (position, arg) in a.iter().enumerate().skip(#idx) {
+                    #check
+                }
+            },
         }
     });
+    // Generate code for checking the number of arguments in a query at parse time:
+    let count_check = match max_args {
+        Some(max_args) if max_args == min_args => quote! {
+            if a.len() != #min_args {
+                return #res::Err(#core::FunctionValidationError::NumberOfArgsMismatch {
+                    expected: #min_args,
+                    received: a.len(),
+                });
+            }
+        },
+        _ => {
+            let max_check = max_args.map(|max_args| {
+                quote! {
+                    if a.len() > #max_args {
+                        return #res::Err(#core::FunctionValidationError::TooManyArgs {
+                            name: String::from(#name_str),
+                            maximum: #max_args,
+                            received: a.len(),
+                        });
+                    }
+                }
+            });
+            quote! {
+                if a.len() < #min_args {
+                    return #res::Err(#core::FunctionValidationError::TooFewArgs {
+                        name: String::from(#name_str),
+                        minimum: #min_args,
+                        received: a.len(),
+                    });
+                }
+                #max_check
+            }
+        }
+    };
     // Generate the validator function used at parse time to validate a function declaration:
     let validator = quote! {
         static #validator_name: #core::Validator = #lazy::new(|| {
             std::boxed::Box::new(|a: &[#core::FunctionExprArg]| {
-                if a.len() != #args_len {
-                    return #res::Err(#core::FunctionValidationError::NumberOfArgsMismatch {
-                        expected: a.len(),
-                        received: #args_len,
-                    });
-                }
+                #count_check
                 #(#arg_checks)*
                 Ok(())
             })
@@ -89,24 +145,125 @@ pub(crate) fn expand(input: ItemFn, name_str: Option<LitStr>) -> Result<Expanded
     };
     // Generate the code to declare each individual argument for evaluation, at query time:
     let arg_declarations = args.iter().map(|arg| {
-        let FnArgument { ident, ty } = arg;
-        // validation should ensure unwrap is okay here:
-        quote! {
-            let #ident = #ty::try_from(v.pop_front().unwrap()).unwrap();
+        let FnArgument {
+            ident,
+            ty,
+            conversion,
+            arity,
+        } = arg;
+        // validation should ensure unwraps are okay here:
+        match arity {
+            Arity::Required => {
+                let declaration = quote! {
+                    let #ident = #ty::try_from(v.pop_front().unwrap()).unwrap();
+                };
+                match conversion {
+                    ArgConversion::None => declaration,
+                    ArgConversion::Logical => quote! {
+                        #declaration
+                        let #ident = bool::from(#ident);
+                    },
+                    // the function produces nothing if the argument can not be converted:
+                    ArgConversion::FromValue => quote! {
+                        #declaration
+                        let std::option::Option::Some(#ident) = #core::FromValueType::from_value_type(&#ident) else {
+                            return #core::JsonPathValue::Nothing;
+                        };
+                    },
+                }
+            }
+            Arity::Optional => {
+                let declaration = quote! {
+                    let #ident = v.pop_front().map(|a| #ty::try_from(a).unwrap());
+                };
+                match conversion {
+                    ArgConversion::None => declaration,
+                    ArgConversion::Logical => quote! {
+                        #declaration
+                        let #ident = #ident.map(bool::from);
+                    },
+                    // the function produces nothing if a given argument can not be converted:
+                    ArgConversion::FromValue => quote! {
+                        #declaration
+                        let #ident = match &#ident {
+                            std::option::Option::Some(a) => match #core::FromValueType::from_value_type(a) {
+                                std::option::Option::Some(a) => std::option::Option::Some(a),
+                                std::option::Option::None => return #core::JsonPathValue::Nothing,
+                            },
+                            std::option::Option::None => std::option::Option::None,
+                        };
+                    },
+                }
+            }
+            Arity::Variadic => {
+                let declaration = quote! {
+                    let #ident: std::vec::Vec<#ty> = v.drain(..).map(|a| #ty::try_from(a).unwrap()).collect();
+                };
+                match conversion {
+                    ArgConversion::None => declaration,
+                    ArgConversion::Logical => quote! {
+                        #declaration
+                        let #ident: std::vec::Vec<bool> = #ident.into_iter().map(bool::from).collect();
+                    },
+                    // the function produces nothing if any of the arguments can not be converted:
+                    ArgConversion::FromValue => quote! {
+                        #declaration
+                        let std::option::Option::Some(#ident) = #ident
+                            .iter()
+                            .map(|a| #core::FromValueType::from_value_type(a))
+                            .collect::<std::option::Option<std::vec::Vec<_>>>() else {
+                            return #core::JsonPathValue::Nothing;
+                        };
+                    },
+                }
+            }
         }
     });
-    // Produce the argument name identifiers:
-    let arg_names = args.iter().map(|arg| {
-        let FnArgument { ident, ty: _ } = arg;
-        ident
-    });
+    let uses_context = context.is_some();
+    // Bind the function context, if the function accepts it:
+    let context_declaration = match context {
+        Some(ident) => quote! {
+            let #ident = ctx;
+        },
+        None => quote! {
+            let _ = ctx;
+        },
+    };
+    // Produce the return value, reporting the error if the function failed:
+    let convert = match output {
+        ReturnConversion::None => quote! { r.into() },
+        ReturnConversion::Logical => quote! { #core::LogicalType::from(r).into() },
+        ReturnConversion::Value => quote! { #core::ValueType::from(r).into() },
+        ReturnConversion::OptionalValue => quote! {
+            match r {
+                std::option::Option::Some(r) => #core::ValueType::from(r).into(),
+                std::option::Option::None => #core::JsonPathValue::Nothing,
+            }
+        },
+    };
+    let call = if fallible {
+        quote! {
+            match #name(#(#params,)*) {
+                #res::Ok(r) => #convert,
+                #res::Err(e) => ctx.fail(e),
+            }
+        }
+    } else {
+        quote! {
+            {
+                let r = #name(#(#params,)*);
+                #convert
+            }
+        }
+    };
     // Generate the evaluator function used to evaluate a function at query time:
     let evaluator = quote! {
         fn #name #generics (#inputs) #ret #block
         static #evaluator_name: #core::Evaluator = #lazy::new(|| {
-            std::boxed::Box::new(|mut v: std::collections::VecDeque<#core::JsonPathValue>| {
+            std::boxed::Box::new(|mut v: std::collections::VecDeque<#core::JsonPathValue>, ctx: &#core::FunctionContext| {
+                #context_declaration
                 #(#arg_declarations)*
-                return #name(#(#arg_names,)*).into()
+                return #call
             })
         });
     };
@@ -119,5 +276,6 @@ pub(crate) fn expand(input: ItemFn, name_str: Option<LitStr>) -> Result<Expanded
         evaluator_name,
         result,
         core,
+        uses_context,
     })
 }
@@ -3,8 +3,9 @@ use std::collections::VecDeque;
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, token::Comma, Error, FnArg, Generics, Pat, PatType,
-    Path, Result, ReturnType, Signature, Type,
+    punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, Error, FnArg,
+    GenericArgument, Generics, Pat, PatType, Path, PathArguments, Result, ReturnType, Signature,
+    Type,
 };
 
 pub struct Components {
@@ -16,13 +17,88 @@ pub struct Components {
     pub ret: ReturnType,
     pub inputs: Punctuated<FnArg, Comma>,
     pub args: VecDeque<FnArgument>,
+    /// The identifier of the `&FunctionContext` parameter, if the function has one
+    pub context: Option<Ident>,
+    /// The identifiers of all parameters, in the order the function declares them
+    pub params: Vec<Ident>,
+    /// Whether the function returns a `Result`, i.e., can fail
+    pub fallible: bool,
+    /// How the value returned by the function is converted to its JSONPath type
+    pub output: ReturnConversion,
 }
 
 pub struct FnArgument {
     pub ident: Ident,
     pub ty: TokenStream,
+    pub conversion: ArgConversion,
+    pub arity: Arity,
 }
 
+/// How many JSONPath arguments a function parameter accepts
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Arity {
+    /// Exactly one argument
+    Required,
+    /// Zero or one argument, declared using `#[optional]` on an `Option<T>` parameter
+    Optional,
+    /// Any number of arguments, declared using `#[variadic]` on a `Vec<T>` parameter
+    Variadic,
+}
+
+/// Determine the arity of a parameter from its attributes, removing them, since they are not
+/// otherwise valid on function parameters
+fn extract_arity(attrs: &mut Vec<Attribute>) -> Result<Arity> {
+    let mut arity = Arity::Required;
+    let mut result = Ok(());
+    attrs.retain(|attr| {
+        let found = if attr.path().is_ident("optional") {
+            Arity::Optional
+        } else if attr.path().is_ident("variadic") {
+            Arity::Variadic
+        } else {
+            return true;
+        };
+        if arity != Arity::Required {
+            result = Err(Error::new(
+                attr.span(),
+                "a parameter can only be one of #[optional] or #[variadic]",
+            ));
+        }
+        arity = found;
+        false
+    });
+    result.map(|_| arity)
+}
+
+/// How a JSONPath argument is converted into the type of a function parameter
+pub enum ArgConversion {
+    /// The parameter is one of the serde_json_path types
+    None,
+    /// The parameter is a `bool`, converted from a `LogicalType`
+    Logical,
+    /// The parameter is a Rust type, or `Option` of one, converted from a `ValueType`
+    FromValue,
+}
+
+/// How the value returned by a function is converted into its JSONPath type
+pub enum ReturnConversion {
+    /// The function returns one of the serde_json_path types
+    None,
+    /// The function returns a `bool`, converted into a `LogicalType`
+    Logical,
+    /// The function returns a Rust type, converted into a `ValueType`
+    Value,
+    /// The function returns an `Option` of a Rust type, where `None` is converted to nothing
+    OptionalValue,
+}
+
+/// The Rust types that can be accepted as parameters, and converted from a `ValueType`
+const ARG_VALUE_TYPES: &[&str] = &["f64", "i64"];
+/// The Rust types that can be accepted as reference parameters, and converted from a `ValueType`
+const ARG_VALUE_REF_TYPES: &[&str] = &["str", "Value", "Number"];
+/// The Rust types that can be returned, and converted into a `ValueType`
+const RETURN_VALUE_TYPES: &[&str] = &["f64", "i64", "String", "Value", "Number"];
+
 fn extract_pat_ident(pat: &Pat) -> Option<Ident> {
     if let Pat::Ident(ref pat_ident) = pat {
         Some(pat_ident.ident.to_owned())
@@ -42,6 +118,116 @@ fn extract_type_path(ty: &Type) -> Option<&Path> {
     }
 }
 
+/// Extract the first type argument `T` of a `wrapper<T, ...>` type, e.g., the success type of a
+/// `Result`, if that is what the type is
+fn extract_wrapped_type<'t>(ty: &'t Type, wrapper: &str) -> Option<&'t Type> {
+    let Type::Path(typepath) = ty else {
+        return None;
+    };
+    let seg = typepath.path.segments.last()?;
+    if seg.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(ref generic_args) = seg.arguments else {
+        return None;
+    };
+    match generic_args.args.first() {
+        Some(GenericArgument::Type(ty)) => Some(ty),
+        _ => None,
+    }
+}
+
+/// Check if the type is a plain type, i.e., not a reference, with one of the given names
+fn is_named_type(ty: &Type, names: &[&str]) -> bool {
+    match ty {
+        Type::Path(ref typepath) if typepath.qself.is_none() => typepath
+            .path
+            .segments
+            .last()
+            .is_some_and(|seg| seg.arguments.is_empty() && names.iter().any(|n| seg.ident == n)),
+        _ => false,
+    }
+}
+
+/// Check if the type is a Rust type that can be converted from a `ValueType`
+fn is_value_arg(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ref typeref) => {
+            typeref.mutability.is_none() && is_named_type(&typeref.elem, ARG_VALUE_REF_TYPES)
+        }
+        _ => is_named_type(ty, ARG_VALUE_TYPES),
+    }
+}
+
+fn core_type(name: &str) -> TokenStream {
+    let ident = Ident::new(name, proc_macro2::Span::call_site());
+    quote! {
+        ::serde_json_path_macros::serde_json_path_core::spec::functions::#ident
+    }
+}
+
+/// Determine the JSONPath type of a parameter, and how it is converted
+fn extract_arg_type(ty: &Type) -> Result<(TokenStream, ArgConversion)> {
+    if is_value_arg(extract_wrapped_type(ty, "Option").unwrap_or(ty)) {
+        return Ok((core_type("ValueType"), ArgConversion::FromValue));
+    }
+    if is_named_type(ty, &["bool"]) {
+        return Ok((core_type("LogicalType"), ArgConversion::Logical));
+    }
+    extract_type_path(ty)
+        .and_then(|path| extract_json_path_type(path).ok())
+        .map(|ts| (ts, ArgConversion::None))
+        .ok_or_else(|| {
+            Error::new(
+            ty.span(),
+            "argument type can only be one of the serde_json_path types: NodesType, ValueType, \
+                or LogicalType, one of the types: &str, f64, i64, &Value, &Number, or an Option of them, \
+                bool, or &FunctionContext",
+            )
+        })
+}
+
+/// Determine the JSONPath type of a return type, and how it is converted
+fn extract_return_type(ty: &Type) -> Result<(TokenStream, ReturnConversion)> {
+    if let Some(inner) = extract_wrapped_type(ty, "Option") {
+        if is_named_type(inner, RETURN_VALUE_TYPES) || is_named_type(inner, &["bool"]) {
+            return Ok((core_type("ValueType"), ReturnConversion::OptionalValue));
+        }
+    }
+    if is_named_type(ty, RETURN_VALUE_TYPES) {
+        return Ok((core_type("ValueType"), ReturnConversion::Value));
+    }
+    if is_named_type(ty, &["bool"]) {
+        return Ok((core_type("LogicalType"), ReturnConversion::Logical));
+    }
+    extract_type_path(ty)
+        .and_then(|path| extract_json_path_type(path).ok())
+        .map(|ts| (ts, ReturnConversion::None))
+        .ok_or_else(|| {
+            Error::new(
+            ty.span(),
+            "return type can only be one of the serde_json_path types: NodesType, ValueType, or \
+                LogicalType, one of the types: f64, i64, String, Value, Number, or an Option of them, \
+                bool, or a Result of any of these",
+            )
+        })
+}
+
+/// Check if the type is a reference to a `FunctionContext`, which is not a JSONPath argument
+fn is_function_context(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ref typeref) => match *typeref.elem {
+            Type::Path(ref typepath) if typepath.qself.is_none() => typepath
+                .path
+                .segments
+                .last()
+                .is_some_and(|seg| seg.ident == "FunctionContext"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 fn extract_json_path_type(p: &Path) -> Result<TokenStream> {
     let p_seg = p
         .segments
@@ -73,10 +259,11 @@ fn extract_json_path_type(p: &Path) -> Result<TokenStream> {
 pub fn extract_components(input: Signature) -> Result<Components> {
     let name = input.ident.clone();
     let generics = input.generics.clone();
-    let inputs = input.inputs.clone();
+    let mut inputs = input.inputs.clone();
     let ret = input.output.clone();
 
-    let result = match &ret {
+    let mut fallible = false;
+    let (result, output) = match &ret {
         ReturnType::Default => {
             return Err(Error::new(
                 input.span(),
@@ -84,27 +271,31 @@ pub fn extract_components(input: Signature) -> Result<Components> {
             ))
         }
         ReturnType::Type(_, ty) => {
-            if let Some(path) = extract_type_path(ty.as_ref()) {
-                extract_json_path_type(path)?
-            } else {
-                return Err(Error::new(
-                    ty.span(),
-                    "return type can only be one of the serde_json_path types: NodesType, \
-                        ValueType, or LogicalType",
-                ));
-            }
+            let ty = match extract_wrapped_type(ty.as_ref(), "Result") {
+                Some(ok) => {
+                    fallible = true;
+                    ok
+                }
+                None => ty.as_ref(),
+            };
+            extract_return_type(ty)?
         }
     };
 
-    let args: Result<VecDeque<FnArgument>> = inputs
-        .iter()
-        .map(|i| match i {
-            FnArg::Receiver(_) => Err(Error::new(
-                inputs.span(),
-                "receiver arguments like self, &self, or &mut self are not supported",
-            )),
+    let mut args = VecDeque::new();
+    let mut context = None;
+    let mut params = Vec::new();
+    let inputs_span = inputs.span();
+    for i in inputs.iter_mut() {
+        match i {
+            FnArg::Receiver(_) => {
+                return Err(Error::new(
+                    inputs_span,
+                    "receiver arguments like self, &self, or &mut self are not supported",
+                ))
+            }
             FnArg::Typed(PatType {
-                attrs: _,
+                attrs,
                 pat,
                 colon_token: _,
                 ty,
@@ -117,21 +308,57 @@ pub fn extract_components(input: Signature) -> Result<Components> {
                         "expected identifier in function argument",
                     ));
                 };
-                let ty = if let Some(path) = extract_type_path(ty) {
-                    extract_json_path_type(path)?
-                } else {
+                params.push(ident.clone());
+                let arity = extract_arity(attrs)?;
+                if is_function_context(ty) {
+                    if arity != Arity::Required {
+                        return Err(Error::new(
+                            ty.span(),
+                            "a &FunctionContext argument can not be #[optional] or #[variadic]",
+                        ));
+                    }
+                    if context.is_some() {
+                        return Err(Error::new(
+                            ty.span(),
+                            "only one &FunctionContext argument is supported",
+                        ));
+                    }
+                    context = Some(ident);
+                    continue;
+                }
+                if args
+                    .back()
+                    .is_some_and(|a: &FnArgument| a.arity > arity || a.arity == Arity::Variadic)
+                {
                     return Err(Error::new(
                         ty.span(),
-                        "argument type can only be one of the serde_json_path types: NodesType, \
-                                ValueType, or LogicalType",
+                        "#[optional] arguments must follow required arguments, and only the last \
+                            argument can be #[variadic]",
                     ));
+                }
+                let inner = match arity {
+                    Arity::Required => Some(ty.as_ref()),
+                    Arity::Optional => extract_wrapped_type(ty, "Option"),
+                    Arity::Variadic => extract_wrapped_type(ty, "Vec"),
                 };
-                Ok(FnArgument { ident, ty })
+                let Some(inner) = inner else {
+                    return Err(Error::new(
+                        ty.span(),
+                        "#[optional] arguments must be an Option<T>, and #[variadic] arguments \
+                            must be a Vec<T>",
+                    ));
+                };
+                let (ty, conversion) = extract_arg_type(inner)?;
+                args.push_back(FnArgument {
+                    ident,
+                    ty,
+                    conversion,
+                    arity,
+                });
             }
-        })
-        .collect();
+        }
+    }
 
-    let args = args?;
     let validator_name = Ident::new(
         format!("{name}_validator").to_uppercase().as_str(),
         name.span(),
@@ -148,6 +375,10 @@ pub fn extract_components(input: Signature) -> Result<Components> {
         inputs,
         ret,
         args,
+        context,
+        params,
+        fallible,
+        output,
         validator_name,
         evaluator_name,
     })
@@ -15,6 +15,7 @@ pub(crate) fn expand(attrs: FunctionMacroArgs, input: ItemFn) -> TokenStream {
         evaluator_name,
         result,
         core,
+        uses_context,
     } = match common::define::expand(input, attrs.name) {
         Ok(exp) => exp,
         Err(err) => return err.into(),
@@ -24,6 +25,9 @@ pub(crate) fn expand(attrs: FunctionMacroArgs, input: ItemFn) -> TokenStream {
         ::serde_json_path_macros::inventory
     };
 
+    // Functions that accept a `&FunctionContext` may need the location of the current node:
+    let using_context = uses_context.then(|| quote! { .using_context() });
+
     TokenStream::from(quote! {
         #validator
         #evaluator
@@ -33,7 +37,7 @@ pub(crate) fn expand(attrs: FunctionMacroArgs, input: ItemFn) -> TokenStream {
                 #result::function_type(),
                 &#evaluator_name,
                 &#validator_name,
-            )
+            )#using_context
         }
     })
 }
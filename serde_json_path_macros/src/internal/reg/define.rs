@@ -17,11 +17,15 @@ pub(crate) fn expand(attrs: RegisterMacroArgs, input: ItemFn) -> TokenStream {
         evaluator_name,
         result,
         core,
+        uses_context,
     } = match common::define::expand(input, name) {
         Ok(exp) => exp,
         Err(err) => return err.into(),
     };
 
+    // Functions that accept a `&FunctionContext` may need the location of the current node:
+    let using_context = uses_context.then(|| quote! { .using_context() });
+
     TokenStream::from(quote! {
         #validator
         #evaluator
@@ -30,6 +34,6 @@ pub(crate) fn expand(attrs: RegisterMacroArgs, input: ItemFn) -> TokenStream {
             #result::function_type(),
             &#evaluator_name,
             &#validator_name,
-        );
+        )#using_context;
     })
 }